@@ -1,7 +1,7 @@
 use proc_macro::TokenStream;
-use proc_macro2::TokenStream as TokenStream2;
+use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::quote;
-use syn::{Ident, LitInt, Token, parse_macro_input};
+use syn::{Ident, LitFloat, LitInt, Token, Visibility, parse_macro_input};
 use nn_utils::layerable::{LayerKind, Layerable};
 
 // Custom parsing for our network DSL
@@ -26,16 +26,51 @@ mod parsing {
         Ok(values)
     }
 
+    // `name` or `name(value)` for activations with a single configurable parameter
+    fn parse_optional_f32(input: ParseStream, default: f32) -> syn::Result<f32> {
+        if !input.peek(::syn::token::Paren) {
+            return Ok(default);
+        }
+
+        let content;
+        ::syn::parenthesized!(content in input);
+        content.parse::<LitFloat>()?.base10_parse()
+    }
+
+    // `(channels, height, width)`; flat widths are `(n, 1, 1)`
+    pub type Shape = (usize, usize, usize);
+
     #[derive(Debug, Clone)]
     pub struct LayerSpec {
         pub input: usize,
+        pub input_shape: Shape,
+        pub output_shape: Shape,
         pub kind: LayerKind,
     }
 
     impl LayerSpec {
-        pub fn new(input: usize, kind: LayerKind) -> Self {
-            Self { input, kind }
+        pub fn new(input_shape: Shape, output_shape: Shape, kind: LayerKind) -> Self {
+            let (c, h, w) = input_shape;
+            Self {
+                input: c * h * w,
+                input_shape,
+                output_shape,
+                kind,
+            }
+        }
+
+        // activations keep the shape they're given
+        fn same(shape: Shape, kind: LayerKind) -> Self {
+            Self::new(shape, shape, kind)
+        }
+    }
+
+    // Spatial size after a window of `kernel` moved by `stride` over `size` padded by `padding`
+    fn window_output(size: usize, kernel: usize, stride: usize, padding: usize) -> Option<usize> {
+        if stride == 0 || kernel == 0 || kernel > size + 2 * padding {
+            return None;
         }
+        Some((size + 2 * padding - kernel) / stride + 1)
     }
 
     impl Layerable for LayerSpec {
@@ -49,16 +84,35 @@ mod parsing {
     }
 
     pub struct NetworkDef {
+        /// Set by a leading `MyNet;` or `pub MyNet;`, which names the struct instead of building
+        /// an anonymous one
+        pub name: Option<(Visibility, Ident)>,
         pub layers: Vec<LayerSpec>,
     }
 
     impl Parse for NetworkDef {
         fn parse(input: ParseStream) -> syn::Result<Self> {
+            let name = if input.peek(Token![pub]) || input.peek2(Token![;]) {
+                let vis = input.parse::<Visibility>()?;
+                let ident = input.parse::<Ident>()?;
+                input.parse::<Token![;]>()?;
+                Some((vis, ident))
+            } else {
+                None
+            };
+
             input.parse::<Ident>()?;
 
+            // `input(n)` for flat inputs, `input(c, h, w)` for feature maps
             let content;
             ::syn::parenthesized!(content in input);
-            let mut cur_size = content.parse::<LitInt>()?.base10_parse()?;
+            let first = content.parse::<LitInt>()?.base10_parse()?;
+            let mut shape = if content.is_empty() {
+                (first, 1, 1)
+            } else {
+                let [h, w] = parse_optional_usizes(&content, [1, 1])?;
+                (first, h, w)
+            };
 
             input.parse::<Token![->]>()?;
 
@@ -66,6 +120,7 @@ mod parsing {
 
             while !input.is_empty() {
                 let layer_name: Ident = input.parse()?;
+                let cur_size = shape.0 * shape.1 * shape.2;
 
                 match layer_name.to_string().as_str() {
                     "dense" => {
@@ -73,27 +128,86 @@ mod parsing {
                         ::syn::parenthesized!(content in input);
                         let next_size = content.parse::<LitInt>()?.base10_parse()?;
                         layers.push(LayerSpec::new(
-                            cur_size,
+                            shape,
+                            (next_size, 1, 1),
                             LayerKind::Dense {
                                 output: next_size,
                             },
                         ));
 
-                        // resize network width
-                        cur_size = next_size;
+                        // resize network width, flattening any feature map
+                        shape = (next_size, 1, 1);
                     }
                     "relu" | "ReLU" => {
-                        layers.push(LayerSpec::new(
-                            cur_size,
+                        layers.push(LayerSpec::same(
+                            shape,
                             LayerKind::ReLU { width: cur_size },
                         ));
                     }
                     "sigmoid" | "Sigmoid" => {
-                        layers.push(LayerSpec::new(
-                            cur_size,
+                        layers.push(LayerSpec::same(
+                            shape,
                             LayerKind::Sigmoid { width: cur_size },
                         ));
                     }
+                    "tanh" | "Tanh" => {
+                        layers.push(LayerSpec::same(
+                            shape,
+                            LayerKind::Tanh { width: cur_size },
+                        ));
+                    }
+                    "leaky_relu" | "LeakyReLU" => {
+                        let slope = parse_optional_f32(input, 0.01)?;
+                        layers.push(LayerSpec::same(
+                            shape,
+                            LayerKind::LeakyReLU {
+                                width: cur_size,
+                                slope,
+                            },
+                        ));
+                    }
+                    "elu" | "ELU" => {
+                        let alpha = parse_optional_f32(input, 1.0)?;
+                        layers.push(LayerSpec::same(
+                            shape,
+                            LayerKind::ELU {
+                                width: cur_size,
+                                alpha,
+                            },
+                        ));
+                    }
+                    "silu" | "SiLU" => {
+                        let beta = parse_optional_f32(input, 1.0)?;
+                        layers.push(LayerSpec::same(
+                            shape,
+                            LayerKind::SiLU {
+                                width: cur_size,
+                                beta,
+                            },
+                        ));
+                    }
+                    "gelu" | "GELU" => {
+                        layers.push(LayerSpec::same(
+                            shape,
+                            LayerKind::GELU { width: cur_size },
+                        ));
+                    }
+                    "softmax" | "Softmax" => {
+                        layers.push(LayerSpec::same(
+                            shape,
+                            LayerKind::Softmax { width: cur_size },
+                        ));
+                    }
+                    "layer_norm" | "LayerNorm" => {
+                        let eps = parse_optional_f32(input, 1e-5)?;
+                        layers.push(LayerSpec::same(
+                            shape,
+                            LayerKind::LayerNorm {
+                                width: cur_size,
+                                eps,
+                            },
+                        ));
+                    }
                     "conv" | "Conv" => {
                         // parse parens with comma-separated ints; allow optional named args later
                         let content;
@@ -108,17 +222,63 @@ mod parsing {
                         let [stride, pad] = parse_optional_usizes(&content, [1, 0])?;
 
                         let out_channels = out_c.base10_parse()?;
+                        let kernel = k.base10_parse()?;
+                        let (Some(h), Some(w)) = (
+                            window_output(shape.1, kernel, stride, pad),
+                            window_output(shape.2, kernel, stride, pad),
+                        ) else {
+                            return Err(::syn::Error::new(
+                                layer_name.span(),
+                                format!(
+                                    "kernel {kernel} with stride {stride} does not fit a {}x{} input with padding {pad}",
+                                    shape.1, shape.2
+                                ),
+                            ));
+                        };
+
+                        let next_shape = (out_channels, h, w);
                         layers.push(LayerSpec::new(
-                            cur_size,
+                            shape,
+                            next_shape,
                             LayerKind::Conv {
                                 out_channels,
-                                kernel: k.base10_parse()?,
+                                kernel,
                                 stride,
                                 padding: pad,
                             },
                         ));
 
-                        cur_size = out_channels;
+                        shape = next_shape;
+                    }
+                    "maxpool" | "MaxPool" | "avgpool" | "AvgPool" => {
+                        // pool(kernel) or pool(kernel, stride), the stride defaulting to the kernel
+                        let content;
+                        ::syn::parenthesized!(content in input);
+                        let kernel = content.parse::<LitInt>()?.base10_parse()?;
+                        let [stride] = parse_optional_usizes(&content, [kernel])?;
+
+                        let (Some(h), Some(w)) = (
+                            window_output(shape.1, kernel, stride, 0),
+                            window_output(shape.2, kernel, stride, 0),
+                        ) else {
+                            return Err(::syn::Error::new(
+                                layer_name.span(),
+                                format!(
+                                    "window {kernel} with stride {stride} does not fit a {}x{} input",
+                                    shape.1, shape.2
+                                ),
+                            ));
+                        };
+
+                        let kind = if layer_name.to_string().eq_ignore_ascii_case("maxpool") {
+                            LayerKind::MaxPool { kernel, stride }
+                        } else {
+                            LayerKind::AvgPool { kernel, stride }
+                        };
+
+                        let next_shape = (shape.0, h, w);
+                        layers.push(LayerSpec::new(shape, next_shape, kind));
+                        shape = next_shape;
                     }
                     "output" => break,
                     _ => return Err(::syn::Error::new(layer_name.span(), "Unknown layer type")),
@@ -133,7 +293,7 @@ mod parsing {
                 }
             }
 
-            Ok(NetworkDef { layers })
+            Ok(NetworkDef { name, layers })
         }
     }
 }
@@ -149,6 +309,7 @@ pub fn network(input: TokenStream) -> TokenStream {
 }
 
 fn generate_network(def: parsing::NetworkDef) -> TokenStream2 {
+    let is_named = def.name.is_some();
     let input_size = def.layers.first().map(|l| l.input()).unwrap_or(0);
     let layer_count = def.layers.len();
 
@@ -157,15 +318,16 @@ fn generate_network(def: parsing::NetworkDef) -> TokenStream2 {
     let mut max_size = input_size;
     let mut layer_io = Vec::with_capacity(layer_count);
     let mut layer_types = Vec::with_capacity(layer_count);
+    let mut layer_inits = Vec::with_capacity(layer_count);
+    let mut layer_forwards = Vec::with_capacity(layer_count);
 
     for layer in &def.layers {
         let kind = layer.kind();
 
-        let next_size = match kind {
-            LayerKind::Dense { output } => output,
-            LayerKind::ReLU { .. } | LayerKind::Sigmoid { .. } => current_size,
-            LayerKind::Conv { out_channels, .. } => out_channels,
-        };
+        // the parser already tracked every layer's shape
+        let (ic, ih, iw) = layer.input_shape;
+        let (oc, oh, ow) = layer.output_shape;
+        let next_size = oc * oh * ow;
 
         layer_io.push((current_size, next_size));
 
@@ -175,10 +337,42 @@ fn generate_network(def: parsing::NetworkDef) -> TokenStream2 {
             }
             LayerKind::ReLU { .. } => quote! { ::nn::network::ReLU<#current_size> },
             LayerKind::Sigmoid { .. } => quote! { ::nn::network::Sigmoid<#current_size> },
-            LayerKind::Conv { .. } => quote! { ::nn::network::Conv<#current_size> },
+            LayerKind::Tanh { .. } => quote! { ::nn::network::Tanh<#current_size> },
+            LayerKind::LeakyReLU { .. } => quote! { ::nn::network::LeakyReLU<#current_size> },
+            LayerKind::ELU { .. } => quote! { ::nn::network::ELU<#current_size> },
+            LayerKind::SiLU { .. } => quote! { ::nn::network::SiLU<#current_size> },
+            LayerKind::GELU { .. } => quote! { ::nn::network::GELU<#current_size> },
+            LayerKind::Softmax { .. } => quote! { ::nn::network::Softmax<#current_size> },
+            LayerKind::LayerNorm { .. } => quote! { ::nn::network::LayerNorm<#current_size> },
+            LayerKind::Conv { kernel, stride, padding, .. } => {
+                quote! { ::nn::conv::Conv<#iw, #ih, #ic, #kernel, #kernel, #oc, #stride, #padding> }
+            }
+            LayerKind::MaxPool { kernel, stride } => {
+                quote! { ::nn::network::MaxPool<#iw, #ih, #ic, #kernel, #stride> }
+            }
+            LayerKind::AvgPool { kernel, stride } => {
+                quote! { ::nn::network::AvgPool<#iw, #ih, #ic, #kernel, #stride> }
+            }
+        };
+
+        // conv layers take typed tensors in `forward`, so the flat buffers go through `forward_slice`
+        let forward = match kind {
+            LayerKind::Conv { .. } => quote! { forward_slice },
+            _ => quote! { forward },
+        };
+
+        // Generate layer initializations
+        let init = match kind {
+            LayerKind::LeakyReLU { slope, .. } => quote! { <#tokens>::new(#slope) },
+            LayerKind::ELU { alpha, .. } => quote! { <#tokens>::new(#alpha) },
+            LayerKind::SiLU { beta, .. } => quote! { <#tokens>::new(#beta) },
+            LayerKind::LayerNorm { eps, .. } => quote! { <#tokens>::new(#eps) },
+            _ => quote! { <#tokens>::init() },
         };
 
         layer_types.push(tokens);
+        layer_inits.push(init);
+        layer_forwards.push(forward);
 
         max_size = max_size.max(next_size);
         current_size = next_size;
@@ -188,114 +382,158 @@ fn generate_network(def: parsing::NetworkDef) -> TokenStream2 {
 
     // Generate forward pass with buffer reuse
     let mut forward_calls = Vec::new();
+    let mut checked_forward_calls = Vec::new();
     let mut use_buf_a = true;
 
-    for (i, (in_size, out_size)) in layer_io.iter().enumerate() {
+    for (i, ((in_size, out_size), forward)) in layer_io.iter().zip(&layer_forwards).enumerate() {
         let layer_idx = ::syn::Index::from(i);
         let (input_buf, output_buf) = if use_buf_a {
-            (quote! { &self._buf_a }, quote! { &mut self._buf_b })
+            (quote! { &workspace.buf_a }, quote! { &mut workspace.buf_b })
         } else {
-            (quote! { &self._buf_b }, quote! { &mut self._buf_a })
+            (quote! { &workspace.buf_b }, quote! { &mut workspace.buf_a })
         };
 
-        // forward_calls.push(quote! {
-        //     self.layers.#layer_idx.forward(
-        //         <&[f32; #current_size]>::try_into(#input_buf[..#current_size]).unwrap(),
-        //         <&mut [f32; #current_size]>::try_into(&mut #output_buf[..#current_size]).unwrap(),
-        //     );
-        // });
-
-        forward_calls.push(quote! {
-            self.layers.#layer_idx.forward(
+        let call = quote! {
+            self.layers.#layer_idx.#forward(
                 #input_buf[..#in_size],
                 #output_buf[..#out_size],
             );
+        };
+        forward_calls.push(call.clone());
+        checked_forward_calls.push(quote! {
+            #call
+            ::nn::network::check_finite(#i, #output_buf[..#out_size])?;
         });
 
         use_buf_a = !use_buf_a;
     }
 
-    // Generate layer initializations
-    let layer_inits = layer_types.iter().map(|layer_type| {
-        quote! { <#layer_type>::init() }
-    });
+    let layer_shapes: Vec<_> = layer_io
+        .iter()
+        .map(|(in_size, out_size)| quote! { (#in_size, #out_size) })
+        .collect();
+    let layer_indices: Vec<_> = (0..layer_count).map(::syn::Index::from).collect();
 
     let final_buffer = if (layer_count % 2) == 1 {
-        quote! { self._buf_b }
+        quote! { workspace.buf_b }
     } else {
-        quote! { self._buf_a }
+        quote! { workspace.buf_a }
     };
 
-    quote! {
-        {
-            #[derive(Debug)]
-            struct Network<Layers> {
-                layers: Layers,
-                // Double buffering approach with fixed-size boxes
-                _buf_a: Box<[f32; #max_size]>,
-                _buf_b: Box<[f32; #max_size]>,
-            }
+    // a named network is declared in place; otherwise it's built inside a block, as `Network`
+    let (vis, name) = def
+        .name
+        .unwrap_or_else(|| (Visibility::Inherited, Ident::new("Network", Span::call_site())));
+    let workspace_name = ::quote::format_ident!("{name}Workspace");
 
-            struct NetworkWorkspace {
+    let items = quote! {
+        #[derive(Debug)]
+        #vis struct #name {
+            #vis layers: (#(#layer_types,)*),
+        }
+
+        /// Ping-pong activation buffers for `forward_with_workspace`, sized for the widest layer
+        #[derive(Debug)]
+        #vis struct #workspace_name {
+            buf_a: Box<[f32; #max_size]>,
+            buf_b: Box<[f32; #max_size]>,
+        }
 
+        impl #workspace_name {
+            fn new() -> Self {
+                #workspace_name {
+                    buf_a: Box::new([0.0; #max_size]),
+                    buf_b: Box::new([0.0; #max_size]),
+                }
             }
+        }
 
-            impl Network<(#(#layer_types,)*)> {
-                pub fn new() -> Self {
-                    Network {
-                        layers: (#(#layer_inits,)*),
-                        _buf_a: Box::new([Default::default(); #max_size]),
-                        _buf_b: Box::new([Default::default(); #max_size]),
-                    }
+        impl #name {
+            pub fn new() -> Self {
+                #name {
+                    layers: (#(#layer_inits,)*),
                 }
+            }
 
-                pub fn forward_with_workspace(&self, input: &[f32; #input_size], workspace: &mut NetworkWorkspace) -> [f32; #output_size] {
-                    // used to be forward<I: AsRef<[f32; #input_size]>>(... input: I)
+            /// Scratch space that can be reused across `forward_with_workspace` calls
+            pub fn workspace(&self) -> #workspace_name {
+                #workspace_name::new()
+            }
 
-                    // Copy input to first buffer
-                    // self._buf_a[..#input_size].copy_from_slice(input);
+            /// Runs the forward pass in `workspace` without allocating
+            pub fn forward_with_workspace(&self, input: &[f32; #input_size], workspace: &mut #workspace_name) -> [f32; #output_size] {
+                workspace.buf_a[..#input_size].copy_from_slice(input);
 
-                    // Run forward pass with ping-pong buffers
-                    // #(#forward_calls)*;
+                // each layer reads one buffer and writes the other
+                #(#forward_calls)*
 
-                    // Extract result from final buffer
-                    let mut result = [0.0; #output_size];
-                    result.copy_from_slice(&(#final_buffer)[..#output_size]);
-                    result
-                }
+                let mut result = [0.0; #output_size];
+                result.copy_from_slice(&(#final_buffer)[..#output_size]);
+                result
+            }
 
-                pub fn forward(&self, input: &[f32; #input_size]) -> [f32; #output_size] {
-                    // Copy input to first buffer
-                    // self.buffers.0 = *input;
+            /// Like `forward`, but checks every layer's output for NaNs and infinities, erroring on
+            /// the first one
+            pub fn forward_checked(&self, input: &[f32; #input_size]) -> Result<[f32; #output_size], ::nn::network::NonFiniteError> {
+                let workspace = &mut self.workspace();
+                workspace.buf_a[..#input_size].copy_from_slice(input);
 
-                    // Run forward pass
-                    // #(#forward_calls)*
+                #(#checked_forward_calls)*
 
-                    // Return final buffer
-                    // #final_buffer
-                    [0.0; #output_size]
-                }
+                let mut result = [0.0; #output_size];
+                result.copy_from_slice(&(#final_buffer)[..#output_size]);
+                Ok(result)
+            }
 
-                pub fn train<D: AsRef<[[f32; #input_size]]>, T: AsRef<[[f32; #output_size]]>>(&mut self, data: D, targets: T) {
-                    // Loop over each case
-                    let targets = targets.as_ref().iter();
-                    let data = data.as_ref().iter();
+            /// Like `forward_with_workspace`, but allocates fresh buffers on every call, which keeps
+            /// the network shareable across threads through `&self`
+            pub fn forward(&self, input: &[f32; #input_size]) -> [f32; #output_size] {
+                self.forward_with_workspace(input, &mut self.workspace())
+            }
 
-                    for (input, target) in data.zip(targets) {
-                        let out = self.forward(input);
-                        let loss: f32 = out.iter().zip(target.iter()).map(|(o, t)| (o - t).powi(2)).sum();
-                        // sum (y hat - y)^2
+            /// Writes a shape header followed by every layer's parameters as little-endian `f32`s
+            pub fn save(&self, w: &mut impl ::std::io::Write) -> ::std::io::Result<()> {
+                ::nn::network::write_header(w, &[#(#layer_shapes),*])?;
+                #(::nn::network::Parameters::save(&self.layers.#layer_indices, w)?;)*
+                Ok(())
+            }
 
-                        // for layer in self.layers.iter() {
+            /// Reads parameters written by `save`, erroring if the saved layer shapes differ from this network's
+            pub fn load(&mut self, r: &mut impl ::std::io::Read) -> ::std::io::Result<()> {
+                ::nn::network::read_header(r, &[#(#layer_shapes),*])?;
+                #(::nn::network::Parameters::load(&mut self.layers.#layer_indices, r)?;)*
+                Ok(())
+            }
 
-                        // }
-                    }
+            pub fn train<D: AsRef<[[f32; #input_size]]>, T: AsRef<[[f32; #output_size]]>>(&mut self, data: D, targets: T) {
+                // Loop over each case
+                let targets = targets.as_ref().iter();
+                let data = data.as_ref().iter();
 
-                    // Training implementation
+                for (input, target) in data.zip(targets) {
+                    let out = self.forward(input);
+                    let loss: f32 = out.iter().zip(target.iter()).map(|(o, t)| (o - t).powi(2)).sum();
+                    // sum (y hat - y)^2
+
+                    // for layer in self.layers.iter() {
+
+                    // }
                 }
+
+                // Training implementation
             }
+        }
+    };
+
+    if is_named {
+        items
+    } else {
+        quote! {
+            {
+                #items
 
-            Network::<(#(#layer_types,)*)>::new()
+                #name::new()
+            }
         }
     }
 }
@@ -1,48 +1,275 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, error::Error, fmt, iter, mem, ops, slice};
+
+mod save;
+pub mod testing;
+
+pub use save::MAGIC;
 
 /// Node identifier for multi-input graphs
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeId(usize);
 
+impl NodeId {
+    /// The id of the node that was (or will be) the `index`th added to its graph, counting from
+    /// zero, which lets generated graphs refer to nodes they haven't added yet
+    pub fn from_index(index: usize) -> Self {
+        Self(index)
+    }
+
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
 /// Multi-input computation graph with optimized performance
-#[derive(Debug)]
+///
+/// Computing writes into the graph's own buffers, so threads sharing a graph each need a clone.
+/// Ops are plain data, which keeps that clone cheap and the graph `Send + Sync`.
+///
+/// With the `serde` feature, the nodes and names (but not the last computed values) can be
+/// serialized with any serde format, alongside the built-in [`MultiGraph::save`].
+///
+/// # Examples
+///
+/// ```rust
+/// let graph = nn_utils::graph! {
+///     inputs: [x, y]
+///     (@x, @y) -> Mul -> @xy
+///     xy -> sin -> @res
+///     output @res
+/// };
+///
+/// let results: Vec<_> = std::thread::scope(|s| {
+///     let handles: Vec<_> = [[1.0, 2.0], [0.5, -3.0]]
+///         .map(|inputs| {
+///             let mut graph = graph.clone();
+///             s.spawn(move || graph.compute(&inputs))
+///         })
+///         .into_iter()
+///         .collect();
+///     handles.into_iter().map(|h| h.join().unwrap()).collect()
+/// });
+///
+/// // d/dt sin((x + t)(y + t)) = cos(xy) (x + y)
+/// assert_eq!(results[0], vec![(2f64.sin(), 2f64.cos() * 3.0)]);
+/// assert_eq!(results[1], vec![((-1.5f64).sin(), (-1.5f64).cos() * -2.5)]);
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MultiGraph {
     nodes: Vec<Node>,
     node_map: HashMap<String, NodeId>,
     next_id: usize,
     /// Pre-allocated buffers for performance
+    #[cfg_attr(feature = "serde", serde(skip))]
     primals: Vec<f64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     tangents: Vec<f64>,
 }
 
 /// Node in the computation graph
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Node {
     Input(String),
+    /// A fixed value whose tangent is always zero
+    Const(f64),
     AfterOperation(Op, Box<[NodeId]>),
     Output(NodeId),
 }
 
 /// Operations that can be performed on nodes
-#[derive(Debug, Clone, Copy)]
+///
+/// Equality is exact on the parameters, so `Scale(f64::NAN)` isn't equal to itself.
+///
+/// # Examples
+///
+/// ```rust
+/// use nn_utils::autodiff::Op;
+///
+/// let graph = nn_utils::graph! { input -> Pow(2) -> scale(3.0) -> sin -> output };
+/// assert_eq!(graph.ops(), [Op::Pow(2), Op::Scale(3.0), Op::Sin]);
+///
+/// assert_ne!(Op::Pow(2), Op::Powf(2.0));
+/// assert_ne!(Op::Clamp { min: 0.0, max: 1.0 }, Op::Clamp { min: 0.0, max: 2.0 });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Op {
     Scale(f64),
     Sin,
     Cos,
+    /// Integer power. `Pow(0)` is constant 1 with derivative 0 everywhere, including at 0; negative
+    /// exponents have a pole at 0, where the value and derivative are infinite
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut constant = nn_utils::graph! { input -> Pow(0) -> output };
+    /// assert_eq!(constant.compute(0.0), (1.0, 0.0));
+    ///
+    /// // f(x) = 1/x, f'(x) = -1/x^2
+    /// let mut recip = nn_utils::graph! { input -> Pow(-1) -> output };
+    /// assert_eq!(recip.compute(2.0), (0.5, -0.25));
+    /// assert_eq!(recip.compute(-0.5), (-2.0, -4.0));
+    /// ```
     Pow(i32),
+    /// Real exponent; fractional powers of negative inputs are NaN, as with [`f64::powf`]
+    Powf(f64),
+    /// Its derivative at zero is taken to be zero
+    Abs,
+    Neg,
+    /// `1 / x`, infinite at zero
+    Recip,
+    Tanh,
+    Exp,
+    /// Natural logarithm; NaN for negative inputs and `-inf` at zero
+    Ln,
+    /// NaN for negative inputs, with an infinite derivative at zero
+    Sqrt,
+    /// Identity on `[min, max]`, boundaries included, and constant (zero derivative) outside
+    ///
+    /// Unlike [`f64::clamp`] this never panics: with `min > max` every input maps to `max`, and a
+    /// NaN bound leaves that side unbounded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut reversed = nn_utils::graph! {
+    ///     input -> clamp(1.0, 0.0) -> output
+    /// };
+    /// assert_eq!(reversed.compute(0.5), (0.0, 0.0));
+    /// assert_eq!(reversed.compute(-2.0), (0.0, 0.0));
+    ///
+    /// let mut floor = nn_utils::graph! {
+    ///     input -> clamp(0.0, f64::NAN) -> output
+    /// };
+    /// assert_eq!(floor.compute(5.0), (5.0, 1.0));
+    /// assert_eq!(floor.compute(-5.0), (0.0, 0.0));
+    /// ```
+    Clamp {
+        min: f64,
+        max: f64,
+    },
     Add,
     Mul,
+    /// Largest input; the tangent flows only through it, or through the first of several equal ones
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut graph = nn_utils::graph! {
+    ///     inputs: [x, y]
+    ///     x -> Pow(2) -> @a
+    ///     y -> Scale(3.0) -> @b
+    ///     (@a, @b) -> Max -> @res
+    ///     output @res
+    /// };
+    ///
+    /// assert_eq!(graph.compute(&[1.0, 1.0]), vec![(3.0, 3.0)]);
+    /// assert_eq!(graph.compute(&[3.0, 1.0]), vec![(9.0, 6.0)]);
+    /// // tied, so only `x^2` contributes
+    /// assert_eq!(graph.compute(&[3.0, 3.0]), vec![(9.0, 6.0)]);
+    /// ```
+    Max,
+    /// Smallest input, with the same tie-breaking as [`Op::Max`]
+    Min,
 }
 
 impl Op {
+    /// [`Op::Pow`] for integer exponents, [`Op::Powf`] for floating point ones
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::autodiff::Op;
+    ///
+    /// assert!(matches!(Op::pow(2), Op::Pow(2)));
+    /// assert!(matches!(Op::pow(0.5), Op::Powf(e) if e == 0.5));
+    /// ```
+    pub fn pow(exp: impl Exponent) -> Self {
+        exp.into_pow()
+    }
+
+    /// The variant's name, without its parameters
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::autodiff::Op;
+    ///
+    /// assert_eq!(Op::Scale(2.0).name(), "Scale");
+    /// assert_eq!(Op::Scale(2.0).params(), [2.0]);
+    ///
+    /// let clamp = Op::Clamp { min: -1.0, max: 1.0 };
+    /// assert_eq!((clamp.name(), clamp.params()), ("Clamp", vec![-1.0, 1.0]));
+    /// assert!(Op::Sin.params().is_empty());
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match self {
+            Op::Scale(_) => "Scale",
+            Op::Sin => "Sin",
+            Op::Cos => "Cos",
+            Op::Pow(_) => "Pow",
+            Op::Powf(_) => "Powf",
+            Op::Abs => "Abs",
+            Op::Neg => "Neg",
+            Op::Recip => "Recip",
+            Op::Tanh => "Tanh",
+            Op::Exp => "Exp",
+            Op::Ln => "Ln",
+            Op::Sqrt => "Sqrt",
+            Op::Clamp { .. } => "Clamp",
+            Op::Add => "Add",
+            Op::Mul => "Mul",
+            Op::Max => "Max",
+            Op::Min => "Min",
+        }
+    }
+
+    /// The variant's parameters in declaration order, empty for ops without any
+    pub fn params(&self) -> Vec<f64> {
+        match *self {
+            Op::Scale(factor) => vec![factor],
+            Op::Pow(exp) => vec![exp.into()],
+            Op::Powf(exp) => vec![exp],
+            Op::Clamp { min, max } => vec![min, max],
+            Op::Sin
+            | Op::Cos
+            | Op::Abs
+            | Op::Neg
+            | Op::Recip
+            | Op::Tanh
+            | Op::Exp
+            | Op::Ln
+            | Op::Sqrt
+            | Op::Add
+            | Op::Mul
+            | Op::Max
+            | Op::Min => Vec::new(),
+        }
+    }
+
     fn compute(self, inputs: &[f64]) -> f64 {
         match self {
             Op::Scale(factor) => inputs[0] * factor,
             Op::Sin => inputs[0].sin(),
             Op::Cos => inputs[0].cos(),
             Op::Pow(exp) => inputs[0].powi(exp),
+            Op::Powf(exp) => inputs[0].powf(exp),
+            Op::Abs => inputs[0].abs(),
+            Op::Neg => -inputs[0],
+            Op::Recip => inputs[0].recip(),
+            Op::Tanh => inputs[0].tanh(),
+            Op::Exp => inputs[0].exp(),
+            Op::Ln => inputs[0].ln(),
+            Op::Sqrt => inputs[0].sqrt(),
+            Op::Clamp { min, max } => inputs[0].max(min).min(max),
             Op::Add => inputs.iter().sum(),
             Op::Mul => inputs.iter().product(),
+            Op::Max => inputs.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            Op::Min => inputs.iter().copied().fold(f64::INFINITY, f64::min),
         }
     }
 
@@ -51,7 +278,28 @@ impl Op {
             Op::Scale(factor) => factor,
             Op::Sin => inputs[0].cos(),
             Op::Cos => -inputs[0].sin(),
+            // `0 * x^-1` would be NaN at zero
+            Op::Pow(0) => 0.0,
             Op::Pow(exp) => exp as f64 * inputs[0].powi(exp - 1),
+            Op::Powf(0.0) => 0.0,
+            Op::Powf(exp) => exp * inputs[0].powf(exp - 1.0),
+            // `f64::signum` would give 1 at zero
+            Op::Abs if inputs[0] == 0.0 => 0.0,
+            Op::Abs => inputs[0].signum(),
+            Op::Neg => -1.0,
+            Op::Recip => -inputs[0].powi(-2),
+            Op::Tanh => 1.0 - inputs[0].tanh().powi(2),
+            Op::Exp => inputs[0].exp(),
+            Op::Ln => inputs[0].recip(),
+            Op::Sqrt => 0.5 / inputs[0].sqrt(),
+            // the identity exactly where clamping leaves the input as it is
+            Op::Clamp { min, max } => {
+                if inputs[0].max(min).min(max) == inputs[0] {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
             Op::Add => 1.0,
             Op::Mul => inputs
                 .iter()
@@ -59,10 +307,38 @@ impl Op {
                 .filter(|(i, _)| *i != input_idx)
                 .map(|(_, &x)| x)
                 .product(),
+            Op::Max | Op::Min => {
+                let selected = self.compute(inputs);
+                let first = inputs.iter().position(|&x| x == selected);
+                if first == Some(input_idx) { 1.0 } else { 0.0 }
+            }
         }
     }
 }
 
+/// Exponent types accepted by [`Op::pow`]
+pub trait Exponent {
+    fn into_pow(self) -> Op;
+}
+
+impl Exponent for i32 {
+    fn into_pow(self) -> Op {
+        Op::Pow(self)
+    }
+}
+
+impl Exponent for f64 {
+    fn into_pow(self) -> Op {
+        Op::Powf(self)
+    }
+}
+
+impl Default for MultiGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MultiGraph {
     pub fn new() -> Self {
         Self {
@@ -82,17 +358,84 @@ impl MultiGraph {
         id
     }
 
+    /// Adds one input per name, in order, e.g. the features `f0..fN` of a row of tabular data
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::autodiff::{MultiGraph, Op};
+    ///
+    /// let mut graph = MultiGraph::new();
+    /// let features = graph.inputs_from(&["f0", "f1", "f2"]);
+    /// let sum = graph.operation(Op::Add, &features);
+    /// graph.output(sum);
+    ///
+    /// assert_eq!(graph.input_names(), ["f0", "f1", "f2"]);
+    /// assert_eq!(graph.compute(&[1.0, 2.0, 3.0]), vec![(6.0, 3.0)]);
+    /// ```
+    pub fn inputs_from(&mut self, names: &[&str]) -> Vec<NodeId> {
+        names
+            .iter()
+            .map(|&name| self.input(name.to_owned()))
+            .collect()
+    }
+
+    pub fn constant(&mut self, value: f64) -> NodeId {
+        let id = NodeId(self.next_id);
+        self.next_id += 1;
+        self.nodes.push(Node::Const(value));
+        id
+    }
+
     pub fn operation<I>(&mut self, op: Op, inputs: I) -> NodeId
     where
         I: AsRef<[NodeId]>,
     {
+        self.operation_from_slice(op, inputs.as_ref())
+    }
+
+    /// Adds `op` applied to `inputs`, copying them into the node
+    ///
+    /// Borrowing lets callers pass arrays or reuse one buffer, so the node's own copy is the only
+    /// allocation; see `examples/graph_build.rs`.
+    pub fn operation_from_slice(&mut self, op: Op, inputs: &[NodeId]) -> NodeId {
         let id = NodeId(self.next_id);
         self.next_id += 1;
-        self.nodes
-            .push(Node::AfterOperation(op, Box::from(inputs.as_ref())));
+        self.nodes.push(Node::AfterOperation(op, Box::from(inputs)));
         id
     }
 
+    /// Labels `node` so its value can be looked up with [`MultiGraph::value_of`]
+    pub fn name(&mut self, node: NodeId, name: String) {
+        self.node_map.insert(name, node);
+    }
+
+    /// The primal and tangent of a named node from the last [`MultiGraph::compute`]
+    ///
+    /// Inputs are named after their identifiers, and `graph!` names each `@node` too.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut graph = nn_utils::graph! {
+    ///     inputs: [x, y]
+    ///     x -> Pow(2) -> @x_sq
+    ///     y -> Sin -> @y_sin
+    ///     (@x_sq, @y_sin) -> Add -> @result
+    ///     output @result
+    /// };
+    /// assert_eq!(graph.value_of("x_sq"), None);
+    ///
+    /// graph.compute(&[3.0, 0.0]);
+    /// assert_eq!(graph.value_of("x_sq"), Some((9.0, 6.0)));
+    /// assert_eq!(graph.value_of("y"), Some((0.0, 1.0)));
+    /// assert_eq!(graph.value_of("nope"), None);
+    /// ```
+    pub fn value_of(&self, name: &str) -> Option<(f64, f64)> {
+        let &NodeId(i) = self.node_map.get(name)?;
+        Some((*self.primals.get(i)?, *self.tangents.get(i)?))
+    }
+
     pub fn output(&mut self, node: NodeId) -> NodeId {
         let id = NodeId(self.next_id);
         self.next_id += 1;
@@ -100,106 +443,453 @@ impl MultiGraph {
         id
     }
 
+    /// How many values [`MultiGraph::compute`] expects
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let graph = nn_utils::graph! {
+    ///     inputs: [x, y, z]
+    ///     (@x, @y, @z) -> Add -> @sum
+    ///     output @sum
+    /// };
+    ///
+    /// assert_eq!((graph.num_inputs(), graph.num_outputs()), (3, 1));
+    /// assert_eq!(graph.input_names(), ["x", "y", "z"]);
+    /// ```
+    pub fn num_inputs(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter(|node| matches!(node, Node::Input(_)))
+            .count()
+    }
+
+    /// How many results [`MultiGraph::compute`] returns
+    pub fn num_outputs(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter(|node| matches!(node, Node::Output(_)))
+            .count()
+    }
+
+    /// Input names in declaration order, which is the order [`MultiGraph::compute`] takes them in
+    pub fn input_names(&self) -> Vec<&str> {
+        self.nodes
+            .iter()
+            .filter_map(|node| match node {
+                Node::Input(name) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Panicking version of [`MultiGraph::compute_checked`]
     pub fn compute(&mut self, inputs: &[f64]) -> Vec<(f64, f64)> {
-        self.primals.clear();
-        self.tangents.clear();
+        self.compute_checked(inputs)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
 
-        // Ensure buffers are large enough
-        let needed_size = self.nodes.len();
-        if self.primals.capacity() < needed_size {
-            self.primals.reserve(needed_size);
-            self.tangents.reserve(needed_size);
+    /// Evaluates every output and its derivative, given one value per input in declaration order
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::autodiff::GraphError;
+    ///
+    /// let mut graph = nn_utils::graph! {
+    ///     inputs: [x, y, z]
+    ///     (@x, @y, @z) -> Add -> @sum
+    ///     output @sum
+    /// };
+    ///
+    /// assert_eq!(graph.compute_checked(&[1.0, 2.0, 3.0]), Ok(vec![(6.0, 3.0)]));
+    ///
+    /// let err = graph.compute_checked(&[1.0, 2.0]).unwrap_err();
+    /// assert_eq!(err, GraphError::MissingInput { name: "z".into(), expected: 3, found: 2 });
+    ///
+    /// let err = graph.compute_checked(&[1.0, 2.0, 3.0, 4.0]).unwrap_err();
+    /// assert_eq!(err, GraphError::ExtraInputs { expected: 3, found: 4 });
+    /// ```
+    pub fn compute_checked(&mut self, inputs: &[f64]) -> Result<Vec<(f64, f64)>, GraphError> {
+        self.check_inputs(inputs)?;
+        self.run(inputs, Some(&mut iter::repeat(&1.0)));
+
+        Ok(self
+            .output_indices()
+            .map(|i| (self.primals[i], self.tangents[i]))
+            .collect())
+    }
+
+    /// Like [`MultiGraph::compute_checked`], but looks each input's value up by name rather than
+    /// taking them in declaration order
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::autodiff::GraphError;
+    /// use std::collections::HashMap;
+    ///
+    /// let mut graph = nn_utils::graph! {
+    ///     inputs: [x, y]
+    ///     x -> Pow(2) -> @x_sq
+    ///     y -> Sin -> @y_sin
+    ///     (@x_sq, @y_sin) -> Add -> @res
+    ///     output @res
+    /// };
+    ///
+    /// let named = HashMap::from([("y", 1.0), ("x", 3.0)]);
+    /// assert_eq!(graph.compute_named(&named), Ok(graph.compute(&[3.0, 1.0])));
+    ///
+    /// let err = graph.compute_named(&HashMap::from([("x", 3.0)])).unwrap_err();
+    /// assert_eq!(err, GraphError::MissingInput { name: "y".into(), expected: 2, found: 1 });
+    ///
+    /// let err = graph.compute_named(&HashMap::from([("x", 3.0), ("z", 1.0)])).unwrap_err();
+    /// assert_eq!(err, GraphError::UnknownInput { name: "z".into() });
+    /// ```
+    pub fn compute_named(
+        &mut self,
+        map: &HashMap<&str, f64>,
+    ) -> Result<Vec<(f64, f64)>, GraphError> {
+        let names = self.input_names();
+
+        // a misspelled name shows up as both, and the unknown one is the more helpful to report
+        if let Some(name) = map.keys().find(|name| !names.contains(name)) {
+            return Err(GraphError::UnknownInput {
+                name: (*name).to_owned(),
+            });
         }
 
-        // Initialize with zeros
+        let inputs = names
+            .iter()
+            .map(|&name| {
+                map.get(name).copied().ok_or(GraphError::MissingInput {
+                    name: name.to_owned(),
+                    expected: names.len(),
+                    found: map.len(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.compute_checked(&inputs)
+    }
+
+    /// Like [`MultiGraph::compute`], but with input `i`'s tangent starting at `seeds[i]` rather
+    /// than 1
+    ///
+    /// The tangents are then the directional derivatives along `seeds` (a Jacobian-vector
+    /// product), and a one-hot `seeds` gives the partial derivatives with respect to one input.
+    ///
+    /// Panics if `seeds` has a different length than `inputs`, or like [`MultiGraph::compute`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut graph = nn_utils::graph! {
+    ///     inputs: [x, y]
+    ///     (@x, @y) -> Mul -> @xy
+    ///     xy -> Sin -> @res
+    ///     output @res
+    /// };
+    ///
+    /// let (inputs, seeds) = ([0.5, 2.0], [0.6, -0.8]);
+    /// let (_, directional) = graph.compute_with_seed(&inputs, &seeds)[0];
+    ///
+    /// let h = 1e-6;
+    /// let shifted = |t: f64| [inputs[0] + t * seeds[0], inputs[1] + t * seeds[1]];
+    /// let numeric = (graph.eval(&shifted(h))[0] - graph.eval(&shifted(-h))[0]) / (2.0 * h);
+    /// assert!((directional - numeric).abs() < 1e-8);
+    ///
+    /// // one-hot seeds pick out the partials, y * cos(xy) and x * cos(xy)
+    /// assert_eq!(graph.compute_with_seed(&inputs, &[1.0, 0.0])[0].1, 2.0 * 1f64.cos());
+    /// assert_eq!(graph.compute_with_seed(&inputs, &[0.0, 1.0])[0].1, 0.5 * 1f64.cos());
+    /// ```
+    pub fn compute_with_seed(&mut self, inputs: &[f64], seeds: &[f64]) -> Vec<(f64, f64)> {
+        self.check_inputs(inputs).unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(seeds.len(), inputs.len(), "expected one seed per input");
+        self.run(inputs, Some(&mut seeds.iter()));
+
+        self.output_indices()
+            .map(|i| (self.primals[i], self.tangents[i]))
+            .collect()
+    }
+
+    /// Evaluates every output without differentiating, e.g. for inference
+    ///
+    /// Panics like [`MultiGraph::compute`] if `inputs` doesn't match the graph's inputs.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut graph = nn_utils::graph! {
+    ///     inputs: [x, y]
+    ///     (@x, @y) -> Mul -> @xy
+    ///     xy -> Sin -> @res
+    ///     output @xy, @res
+    /// };
+    ///
+    /// let values = graph.eval(&[2.0, 3.0]);
+    /// assert_eq!(values, vec![6.0, 6f64.sin()]);
+    /// assert_eq!(values, graph.compute(&[2.0, 3.0]).iter().map(|&(p, _)| p).collect::<Vec<_>>());
+    /// ```
+    pub fn eval(&mut self, inputs: &[f64]) -> Vec<f64> {
+        self.check_inputs(inputs).unwrap_or_else(|e| panic!("{e}"));
+        self.run(inputs, None);
+
+        self.output_indices().map(|i| self.primals[i]).collect()
+    }
+
+    fn check_inputs(&self, inputs: &[f64]) -> Result<(), GraphError> {
+        let names = self.input_names();
+
+        let (expected, found) = (names.len(), inputs.len());
+        if found < expected {
+            return Err(GraphError::MissingInput {
+                name: names[found].to_owned(),
+                expected,
+                found,
+            });
+        }
+        if found > expected {
+            return Err(GraphError::ExtraInputs { expected, found });
+        }
+
+        Ok(())
+    }
+
+    // Fills `primals` for every node, and `tangents` too if given a seed tangent per input. Nodes
+    // only ever refer to earlier nodes, so a single pass in insertion order is a topological
+    // traversal.
+    fn run(&mut self, inputs: &[f64], mut seeds: Option<&mut dyn Iterator<Item = &f64>>) {
+        let needed_size = self.nodes.len();
+        let with_tangents = seeds.is_some();
+
+        self.primals.clear();
+        self.tangents.clear();
         self.primals.resize(needed_size, 0.0);
-        self.tangents.resize(needed_size, 0.0);
-
-        // Create a mapping from input names to their indices in the inputs array
-        let mut input_indices = HashMap::new();
-        let mut input_count = 0;
-        for node in &self.nodes {
-            if let Node::Input(name) = node {
-                input_indices.insert(name.clone(), input_count);
-                input_count += 1;
-            }
+        if with_tangents {
+            self.tangents.resize(needed_size, 0.0);
         }
 
-        // First pass: handle inputs
+        // inputs take their values in declaration order
+        let mut next_input = inputs.iter();
+
         for (i, node) in self.nodes.iter().enumerate() {
-            if let Node::Input(name) = node {
-                if let Some(&input_idx) = input_indices.get(name) {
-                    if input_idx < inputs.len() {
-                        self.primals[i] = inputs[input_idx];
-                        self.tangents[i] = 1.0;
-                    } else {
-                        // Handle case where input index is out of bounds
-                        self.primals[i] = 0.0;
-                        self.tangents[i] = 0.0;
+            match node {
+                Node::Input(_) => {
+                    self.primals[i] = *next_input.next().expect("checked by `check_inputs`");
+                    if let Some(seeds) = seeds.as_mut() {
+                        self.tangents[i] = *seeds.next().expect("one seed per input");
                     }
-                } else {
-                    // Handle case where input name is not found
-                    self.primals[i] = 0.0;
-                    self.tangents[i] = 0.0;
                 }
-            }
-        }
+                // constants keep a zero tangent
+                Node::Const(value) => self.primals[i] = *value,
+                Node::AfterOperation(op, ids) => {
+                    let input_primals: Vec<f64> = ids.iter().map(|id| self.primals[id.0]).collect();
+                    self.primals[i] = op.compute(&input_primals);
 
-        // Second pass: handle operations (topological order)
-        for (i, node) in self.nodes.iter().enumerate() {
-            if let Node::AfterOperation(op, inputs) = node {
-                // Pre-allocate input_primals to avoid repeated allocations
-                let mut input_primals = Vec::with_capacity(inputs.len());
-                for &id in inputs {
-                    if id.0 < self.primals.len() {
-                        input_primals.push(self.primals[id.0]);
-                    } else {
-                        input_primals.push(0.0);
+                    // Compute derivatives using chain rule
+                    if with_tangents {
+                        self.tangents[i] = ids
+                            .iter()
+                            .enumerate()
+                            .map(|(j, id)| {
+                                self.tangents[id.0] * op.compute_derivative(&input_primals, j)
+                            })
+                            .sum();
                     }
                 }
-
-                self.primals[i] = op.compute(&input_primals);
-
-                // Compute derivatives using chain rule
-                let mut total_derivative = 0.0;
-                for (j, &input_id) in inputs.iter().enumerate() {
-                    if input_id.0 < self.tangents.len() {
-                        let partial = op.compute_derivative(&input_primals, j);
-                        total_derivative += self.tangents[input_id.0] * partial;
+                Node::Output(id) => {
+                    self.primals[i] = self.primals[id.0];
+                    if with_tangents {
+                        self.tangents[i] = self.tangents[id.0];
                     }
                 }
-                self.tangents[i] = total_derivative;
             }
         }
+    }
 
-        // Third pass: handle outputs
+    /// Reorders the nodes so that each comes after the nodes it reads, which [`MultiGraph::compute`]
+    /// relies on
+    ///
+    /// Only needed when nodes were added before the nodes they refer to. Inputs keep their
+    /// relative order, and so the order `compute` expects their values in. Every other node may
+    /// move, so `NodeId`s from before this call are invalidated; named nodes stay reachable
+    /// through [`MultiGraph::value_of`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::autodiff::{GraphError, MultiGraph, NodeId, Op};
+    ///
+    /// let mut graph = MultiGraph::new();
+    /// let x = graph.input("x".to_string());
+    /// // the output refers to the `Sin` added right after it
+    /// graph.output(NodeId::from_index(2));
+    /// let sin = graph.operation(Op::Sin, [x]);
+    /// graph.name(sin, "sin".to_string());
+    ///
+    /// graph.finalize().unwrap();
+    /// assert_eq!(graph.compute(&[0.0]), vec![(0.0, 1.0)]);
+    /// assert_eq!(graph.value_of("sin"), Some((0.0, 1.0)));
+    ///
+    /// let mut cyclic = MultiGraph::new();
+    /// let a = cyclic.operation(Op::Sin, [NodeId::from_index(1)]);
+    /// cyclic.operation(Op::Cos, [a]);
+    /// assert!(matches!(cyclic.finalize(), Err(GraphError::Cycle { .. })));
+    /// ```
+    pub fn finalize(&mut self) -> Result<(), GraphError> {
+        fn reads(node: &Node) -> &[NodeId] {
+            match node {
+                Node::AfterOperation(_, ids) => ids,
+                Node::Output(id) => slice::from_ref(id),
+                Node::Input(_) | Node::Const(_) => &[],
+            }
+        }
+
+        let n = self.nodes.len();
+        if let Some(&node) = self.nodes.iter().flat_map(reads).find(|id| id.0 >= n) {
+            return Err(GraphError::UnknownNode { node });
+        }
+
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            Unvisited,
+            InProgress,
+            Done,
+        }
+
+        let mut marks = vec![Mark::Unvisited; n];
+        let mut order = Vec::with_capacity(n);
+
+        // inputs and constants read nothing, so they can all go first in their original order
         for (i, node) in self.nodes.iter().enumerate() {
-            if let Node::Output(input_id) = node {
-                if input_id.0 < self.primals.len() {
-                    self.primals[i] = self.primals[input_id.0];
-                    self.tangents[i] = self.tangents[input_id.0];
-                } else {
-                    self.primals[i] = 0.0;
-                    self.tangents[i] = 0.0;
+            if reads(node).is_empty() {
+                marks[i] = Mark::Done;
+                order.push(i);
+            }
+        }
+
+        // depth-first, emitting a node once everything it reads has been emitted
+        for root in 0..n {
+            if marks[root] != Mark::Unvisited {
+                continue;
+            }
+            marks[root] = Mark::InProgress;
+            let mut stack = vec![(root, 0)];
+
+            while let Some(&(i, next)) = stack.last() {
+                match reads(&self.nodes[i]).get(next) {
+                    Some(&NodeId(dep)) => {
+                        stack.last_mut().expect("just peeked").1 += 1;
+                        match marks[dep] {
+                            Mark::Unvisited => {
+                                marks[dep] = Mark::InProgress;
+                                stack.push((dep, 0));
+                            }
+                            Mark::InProgress => {
+                                return Err(GraphError::Cycle { node: NodeId(dep) });
+                            }
+                            Mark::Done => {}
+                        }
+                    }
+                    None => {
+                        marks[i] = Mark::Done;
+                        order.push(i);
+                        stack.pop();
+                    }
                 }
             }
         }
 
-        // Collect outputs
+        let mut new_index = vec![0; n];
+        for (new, &old) in order.iter().enumerate() {
+            new_index[old] = new;
+        }
+        let remap = |id: &NodeId| NodeId(new_index[id.0]);
+
+        let mut old_nodes: Vec<Option<Node>> =
+            mem::take(&mut self.nodes).into_iter().map(Some).collect();
+        self.nodes = order
+            .iter()
+            .map(
+                |&old| match old_nodes[old].take().expect("each node is ordered once") {
+                    Node::AfterOperation(op, ids) => {
+                        Node::AfterOperation(op, ids.iter().map(remap).collect())
+                    }
+                    Node::Output(id) => Node::Output(remap(&id)),
+                    node => node,
+                },
+            )
+            .collect();
+
+        for id in self.node_map.values_mut() {
+            *id = remap(id);
+        }
+
+        // values from an earlier `compute` are indexed the old way
+        self.primals.clear();
+        self.tangents.clear();
+
+        Ok(())
+    }
+
+    fn output_indices(&self) -> impl Iterator<Item = usize> + '_ {
         self.nodes
             .iter()
             .enumerate()
-            .filter_map(|(i, node)| {
-                if matches!(node, Node::Output(_)) {
-                    Some((self.primals[i], self.tangents[i]))
-                } else {
-                    None
-                }
-            })
-            .collect()
+            .filter_map(|(i, node)| matches!(node, Node::Output(_)).then_some(i))
+    }
+}
+
+/// Error from [`MultiGraph::compute_checked`] or [`MultiGraph::compute_named`] when the inputs
+/// don't match the graph's, or from [`MultiGraph::finalize`] when the nodes can't be ordered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    /// Fewer values than inputs; `name` is the first input left without one
+    MissingInput {
+        name: String,
+        expected: usize,
+        found: usize,
+    },
+    /// More values than inputs
+    ExtraInputs { expected: usize, found: usize },
+    /// A value was given by name for `name`, which isn't an input
+    UnknownInput { name: String },
+    /// `node` depends on itself
+    Cycle { node: NodeId },
+    /// A node refers to `node`, which was never added
+    UnknownNode { node: NodeId },
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::MissingInput {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "no value for input `{name}`: the graph has {expected} inputs but {found} values were given"
+            ),
+            GraphError::ExtraInputs { expected, found } => write!(
+                f,
+                "the graph has {expected} inputs but {found} values were given"
+            ),
+            GraphError::UnknownInput { name } => {
+                write!(f, "a value was given for `{name}`, which is not an input")
+            }
+            GraphError::Cycle { node } => {
+                write!(f, "node {} is part of a cycle", node.0)
+            }
+            GraphError::UnknownNode { node } => {
+                write!(f, "node {} is referred to but was never added", node.0)
+            }
+        }
     }
 }
 
+impl Error for GraphError {}
+
 /// Legacy single-input computation graph (kept for backward compatibility)
 #[derive(Clone, Debug)]
 pub struct CompGraph {
@@ -219,6 +909,40 @@ impl CompGraph {
         }
     }
 
+    /// Appends `op` to the end of the chain
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::autodiff::{CompGraph, Op};
+    ///
+    /// let mut graph = CompGraph::new(Vec::new());
+    /// graph.push(Op::Sin);
+    /// graph.push(Op::Cos);
+    /// assert_eq!(graph.compute(0.5), CompGraph::new(vec![Op::Sin, Op::Cos]).compute(0.5));
+    ///
+    /// assert!(matches!(graph.pop(), Some(Op::Cos)));
+    /// assert_eq!(graph.compute(0.5), CompGraph::new(vec![Op::Sin]).compute(0.5));
+    /// ```
+    pub fn push(&mut self, op: Op) {
+        self.ops.push(op);
+
+        let cap = self.ops.len() + 1;
+        self._buf_primals
+            .reserve(cap.saturating_sub(self._buf_primals.len()));
+        self._buf_tangents
+            .reserve(cap.saturating_sub(self._buf_tangents.len()));
+    }
+
+    /// Removes and returns the last op, if any
+    pub fn pop(&mut self) -> Option<Op> {
+        self.ops.pop()
+    }
+
+    pub fn ops(&self) -> &[Op] {
+        &self.ops
+    }
+
     pub fn compute(&mut self, input: f64) -> (f64, f64) {
         self._buf_primals.clear();
         self._buf_tangents.clear();
@@ -238,20 +962,164 @@ impl CompGraph {
     }
 }
 
+/// The chain in `graph!`'s single-input syntax
+///
+/// # Examples
+///
+/// ```rust
+/// let graph = nn_utils::graph! {
+///     input -> Pow(2) -> Cos -> Scale((1.0 / 3.0)) -> Scale(3.0) -> output
+/// };
+/// assert_eq!(
+///     graph.to_string(),
+///     "input -> Pow(2) -> Cos -> Scale(0.3333333333333333) -> Scale(3.0) -> output"
+/// );
+///
+/// let clamped = nn_utils::graph! {
+///     input -> Sin -> clamp(0.0, 1.0) -> output
+/// };
+/// assert_eq!(clamped.to_string(), "input -> Sin -> Clamp(0.0, 1.0) -> output");
+/// ```
+impl fmt::Display for CompGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "input")?;
+        for op in &self.ops {
+            write!(f, " -> {}", op.name())?;
+
+            let params = op.params();
+            if !params.is_empty() {
+                // `Pow` takes an integer, which `{:?}` would print as `2.0`
+                let params: Vec<_> = params
+                    .iter()
+                    .map(|p| match op {
+                        Op::Pow(_) => p.to_string(),
+                        _ => format!("{p:?}"),
+                    })
+                    .collect();
+                write!(f, "({})", params.join(", "))?;
+            }
+        }
+        write!(f, " -> output")
+    }
+}
+
+/// A dual number `re + eps·ε` with `ε² = 0`, for forward-mode differentiation of plain Rust code
+///
+/// Evaluating a function on [`Dual::variable`] carries the derivative along in `eps`.
+///
+/// # Examples
+///
+/// ```rust
+/// use nn_utils::autodiff::Dual;
+///
+/// let f = |x: Dual| (x * x).sin();
+///
+/// let x = 1.5;
+/// let y = f(Dual::variable(x));
+/// assert_eq!(y.re, (x * x).sin());
+/// assert!((y.eps - 2.0 * x * (x * x).cos()).abs() < 1e-12);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dual {
+    pub re: f64,
+    pub eps: f64,
+}
+
+impl Dual {
+    pub fn new(re: f64, eps: f64) -> Self {
+        Self { re, eps }
+    }
+
+    /// The point to differentiate at, with unit tangent
+    pub fn variable(re: f64) -> Self {
+        Self::new(re, 1.0)
+    }
+
+    /// A value that doesn't depend on the variable
+    pub fn constant(re: f64) -> Self {
+        Self::new(re, 0.0)
+    }
+
+    pub fn sin(self) -> Self {
+        Self::new(self.re.sin(), self.eps * self.re.cos())
+    }
+
+    pub fn cos(self) -> Self {
+        Self::new(self.re.cos(), -self.eps * self.re.sin())
+    }
+
+    pub fn exp(self) -> Self {
+        let exp = self.re.exp();
+        Self::new(exp, self.eps * exp)
+    }
+
+    pub fn ln(self) -> Self {
+        Self::new(self.re.ln(), self.eps / self.re)
+    }
+
+    pub fn powi(self, n: i32) -> Self {
+        Self::new(self.re.powi(n), self.eps * n as f64 * self.re.powi(n - 1))
+    }
+}
+
+impl From<f64> for Dual {
+    fn from(re: f64) -> Self {
+        Self::constant(re)
+    }
+}
+
+impl ops::Add for Dual {
+    type Output = Dual;
+    fn add(self, rhs: Dual) -> Dual {
+        Dual::new(self.re + rhs.re, self.eps + rhs.eps)
+    }
+}
+
+impl ops::Sub for Dual {
+    type Output = Dual;
+    fn sub(self, rhs: Dual) -> Dual {
+        Dual::new(self.re - rhs.re, self.eps - rhs.eps)
+    }
+}
+
+impl ops::Mul for Dual {
+    type Output = Dual;
+    fn mul(self, rhs: Dual) -> Dual {
+        Dual::new(self.re * rhs.re, self.eps * rhs.re + self.re * rhs.eps)
+    }
+}
+
+impl ops::Div for Dual {
+    type Output = Dual;
+    fn div(self, rhs: Dual) -> Dual {
+        Dual::new(
+            self.re / rhs.re,
+            (self.eps * rhs.re - self.re * rhs.eps) / (rhs.re * rhs.re),
+        )
+    }
+}
+
+impl ops::Neg for Dual {
+    type Output = Dual;
+    fn neg(self) -> Dual {
+        Dual::new(-self.re, -self.eps)
+    }
+}
+
 /// Macro for building computation graphs
 ///
 /// # Examples
 ///
 /// Single input graph:
 /// ```rust
-/// let graph = nn::graph! {
+/// let graph = nn_utils::graph! {
 ///     input -> Sin -> Cos -> output
 /// };
 /// ```
 ///
 /// Multi-input graph:
 /// ```rust
-/// let graph = nn::graph! {
+/// let graph = nn_utils::graph! {
 ///     inputs: [x, y]
 ///     x -> Pow(2) -> @x_sq
 ///     y -> Sin -> @y_sin
@@ -262,7 +1130,7 @@ impl CompGraph {
 ///
 /// Mixed graph (operations without intermediate names):
 /// ```rust
-/// let graph = nn::graph! {
+/// let graph = nn_utils::graph! {
 ///     inputs: [x, y]
 ///     x -> Pow(2) -> @temp1
 ///     y -> Cos -> @temp2
@@ -271,6 +1139,159 @@ impl CompGraph {
 /// };
 /// ```
 ///
+/// Several outputs, computed in the order they are listed:
+/// ```rust
+/// let mut graph = nn_utils::graph! {
+///     inputs: [x]
+///     x -> Sin -> @s
+///     x -> Cos -> @c
+///     output @s, @c
+/// };
+///
+/// let [(sin, dsin), (cos, dcos)] = graph.compute(&[0.0])[..] else { panic!() };
+/// assert_eq!((sin, dsin), (0.0, 1.0));
+/// assert_eq!((cos, dcos), (1.0, -0.0));
+/// ```
+///
+/// Adding or multiplying by a constant doesn't need an extra input:
+/// ```rust
+/// let mut graph = nn_utils::graph! {
+///     inputs: [x]
+///     x -> mul_const(2.0) -> @doubled
+///     doubled -> add_const(3.0) -> @res
+///     output @res
+/// };
+///
+/// // f(x) = 2x + 3
+/// assert_eq!(graph.compute(&[1.0]), vec![(5.0, 2.0)]);
+/// ```
+///
+/// Ops can also be written in lowercase, e.g. `sin` for [`Op::Sin`] or `scale(2.0)` for
+/// [`Op::Scale`]:
+/// ```rust
+/// let mut capitalized = nn_utils::graph! {
+///     input -> Pow(2) -> Cos -> Scale((1.0 / 3.0)) -> Scale((1.0 / 3.0)) -> Scale(3.0) -> output
+/// };
+/// let mut lowercase = nn_utils::graph! {
+///     input -> pow(2) -> cos -> scale(1.0 / 3.0) -> scale(1.0 / 3.0) -> scale(3.0) -> output
+/// };
+///
+/// let (value, derivative) = capitalized.compute(2.0);
+/// assert_eq!(lowercase.compute(2.0), (value, derivative));
+/// assert!((value - 4f64.cos() / 3.0).abs() < 1e-12);
+/// assert!((derivative + 4.0 * 4f64.sin() / 3.0).abs() < 1e-12);
+///
+/// let mut graph = nn_utils::graph! {
+///     inputs: [x, y]
+///     x -> sin -> @a
+///     (@a, @y) -> mul -> @res
+///     output @res
+/// };
+/// assert_eq!(graph.compute(&[0.0, 2.0]), vec![(0.0, 2.0)]);
+/// ```
+///
+/// Longer scalar chains can use the remaining unary ops the same way:
+/// ```rust
+/// let mut identity = nn_utils::graph! { input -> exp -> ln -> output };
+/// let (value, derivative) = identity.compute(0.7);
+/// assert!((value - 0.7).abs() < 1e-12 && (derivative - 1.0).abs() < 1e-12);
+///
+/// // f(x) = sqrt(|tanh(-x)|), f'(x) = (1 - tanh²(x)) / (2 sqrt(tanh(x))) for x > 0
+/// let mut chain = nn_utils::graph! { input -> neg -> tanh -> abs -> sqrt -> recip -> recip -> output };
+/// let (value, derivative) = chain.compute(1.0);
+/// assert!((value - 1f64.tanh().sqrt()).abs() < 1e-12);
+/// assert!((derivative - (1.0 - 1f64.tanh().powi(2)) / (2.0 * 1f64.tanh().sqrt())).abs() < 1e-12);
+/// ```
+///
+/// `pow(e)` picks the op from the literal: integers keep [`Op::Pow`], floats use [`Op::Powf`].
+/// Fractional exponents are only defined for non-negative inputs:
+/// ```rust
+/// let mut graph = nn_utils::graph! {
+///     inputs: [x]
+///     x -> pow(0.5) -> @root
+///     output @root
+/// };
+///
+/// // f(x) = x^0.5, f'(x) = 0.5 / sqrt(x)
+/// assert_eq!(graph.compute(&[4.0]), vec![(2.0, 0.25)]);
+/// assert!(graph.compute(&[-4.0])[0].0.is_nan());
+///
+/// let mut graph = nn_utils::graph! {
+///     input -> pow(3) -> output
+/// };
+/// assert_eq!(graph.compute(2.0), (8.0, 12.0));
+/// ```
+///
+/// `clamp(min, max)` builds an [`Op::Clamp`], whose derivative is zero once it saturates:
+/// ```rust
+/// let mut graph = nn_utils::graph! {
+///     inputs: [x]
+///     x -> Scale(2.0) -> @y
+///     y -> clamp(0.0, 1.0) -> @res
+///     output @res
+/// };
+///
+/// assert_eq!(graph.compute(&[0.25]), vec![(0.5, 2.0)]);
+/// assert_eq!(graph.compute(&[3.0]), vec![(1.0, 0.0)]);
+/// assert_eq!(graph.compute(&[-3.0]), vec![(0.0, 0.0)]);
+/// ```
+///
+/// A node can feed any number of later operations, including the same one twice; the tangents
+/// from each use are summed:
+/// ```rust
+/// let mut graph = nn_utils::graph! {
+///     inputs: [x]
+///     x -> Pow(2) -> @a
+///     (@a, @a) -> Mul -> @b
+///     (@a, @b) -> Add -> @res
+///     output @res
+/// };
+///
+/// // f(x) = x^2 + x^4, f'(x) = 2x + 4x^3
+/// assert_eq!(graph.compute(&[2.0]), vec![(20.0, 36.0)]);
+/// ```
+///
+/// That includes inputs, so `x * x` differentiates to `2x`:
+/// ```rust
+/// let mut graph = nn_utils::graph! {
+///     inputs: [x]
+///     (@x, @x) -> Mul -> @sq
+///     output @sq
+/// };
+///
+/// assert_eq!(graph.compute(&[3.0]), vec![(9.0, 6.0)]);
+/// ```
+///
+/// Parenthesized inputs can be any number of nodes, and the result can go straight to an output
+/// without being named:
+/// ```rust
+/// let mut graph = nn_utils::graph! {
+///     inputs: [a, b, c, d]
+///     (@a, @b, @c, @d) -> add -> @sum
+///     (@a, @b, @c, @d) -> mul -> output
+///     output @sum
+/// };
+///
+/// // outputs come in the order they're declared
+/// let [(product, d_product), (sum, d_sum)] = graph.compute(&[1.0, 2.0, 3.0, 4.0])[..] else {
+///     unreachable!()
+/// };
+/// assert_eq!((sum, d_sum), (10.0, 4.0));
+/// // d/dt of (1 + t)(2 + t)(3 + t)(4 + t) at t = 0
+/// assert_eq!((product, d_product), (24.0, 50.0));
+/// ```
+///
+/// Every `@node` is a `let` binding, so using one before the line that defines it is a compile
+/// error rather than a silently wrong graph:
+/// ```rust,compile_fail
+/// let graph = nn_utils::graph! {
+///     inputs: [x]
+///     (@a, @a) -> Mul -> @b
+///     x -> Pow(2) -> @a
+///     output @b
+/// };
+/// ```
+///
 /// # Performance Notes
 ///
 /// The implementation uses pre-allocated buffers to minimize memory allocations
@@ -306,10 +1327,26 @@ macro_rules! graph {
     };
 
     // Linear building (single input)
+    (@build_linear [$($ops:expr,)*], pow ( $e:expr ) -> $($rest:tt)*) => {
+        $crate::graph! {
+            @build_linear
+            [$($ops,)* Op::pow($e),],
+            $($rest)*
+        }
+    };
+
+    (@build_linear [$($ops:expr,)*], clamp ( $min:expr, $max:expr ) -> $($rest:tt)*) => {
+        $crate::graph! {
+            @build_linear
+            [$($ops,)* Op::Clamp { min: $min, max: $max },],
+            $($rest)*
+        }
+    };
+
     (@build_linear [$($ops:expr,)*], $op:ident -> $($rest:tt)*) => {
         $crate::graph! {
             @build_linear
-            [$($ops,)* Op::$op,],
+            [$($ops,)* $crate::__graph_op!($op),],
             $($rest)*
         }
     };
@@ -317,7 +1354,7 @@ macro_rules! graph {
     (@build_linear [$($ops:expr,)*], $op:ident ( $($op_args:tt)* ) -> $($rest:tt)*) => {
         $crate::graph! {
             @build_linear
-            [$($ops,)* Op::$op($($op_args)*),],
+            [$($ops,)* $crate::__graph_op!($op($($op_args)*)),],
             $($rest)*
         }
     };
@@ -326,34 +1363,110 @@ macro_rules! graph {
         CompGraph::new(Vec::from([$($ops,)*]))
     };
 
+    // Affine ops against a literal, which becomes a `Node::Const`
+    (@build_multi $graph:ident, $node:ident -> add_const ( $c:expr ) -> @ $result:ident $($rest:tt)*) => {
+        let $result = {
+            let c = $graph.constant($c);
+            $graph.operation(Op::Add, [$node, c])
+        };
+        $graph.name($result, stringify!($result).to_string());
+        $crate::graph! { @build_multi $graph, $($rest)* }
+    };
+
+    (@build_multi $graph:ident, $node:ident -> mul_const ( $c:expr ) -> @ $result:ident $($rest:tt)*) => {
+        let $result = {
+            let c = $graph.constant($c);
+            $graph.operation(Op::Mul, [$node, c])
+        };
+        $graph.name($result, stringify!($result).to_string());
+        $crate::graph! { @build_multi $graph, $($rest)* }
+    };
+
+    (@build_multi $graph:ident, $node:ident -> pow ( $e:expr ) -> @ $result:ident $($rest:tt)*) => {
+        let $result = $graph.operation(Op::pow($e), [$node]);
+        $graph.name($result, stringify!($result).to_string());
+        $crate::graph! { @build_multi $graph, $($rest)* }
+    };
+
+    (@build_multi $graph:ident, $node:ident -> clamp ( $min:expr, $max:expr ) -> @ $result:ident $($rest:tt)*) => {
+        let $result = $graph.operation(Op::Clamp { min: $min, max: $max }, [$node]);
+        $graph.name($result, stringify!($result).to_string());
+        $crate::graph! { @build_multi $graph, $($rest)* }
+    };
+
     (@build_multi $graph:ident, $node:ident -> $op:ident -> @ $result:ident $($rest:tt)*) => {
-        let $result = $graph.operation(Op::$op, vec![$node]);
+        let $result = $graph.operation($crate::__graph_op!($op), [$node]);
+        $graph.name($result, stringify!($result).to_string());
         $crate::graph! { @build_multi $graph, $($rest)* }
     };
 
     (@build_multi $graph:ident, $node:ident -> $op:ident ( $($op_args:tt)* ) -> @ $result:ident $($rest:tt)*) => {
-        let $result = $graph.operation(Op::$op($($op_args)*), vec![$node]);
+        let $result = $graph.operation($crate::__graph_op!($op($($op_args)*)), [$node]);
+        $graph.name($result, stringify!($result).to_string());
         $crate::graph! { @build_multi $graph, $($rest)* }
     };
 
     // Generic N-ary op without extra args: (@a, @b, @c) -> add -> @result
     (@build_multi $graph:ident, ( $( @ $node:ident ),+ ) -> $op:ident -> @ $result:ident $($rest:tt)*) => {
-        let $result = $graph.operation(Op::$op, vec![$($node),+]);
+        let $result = $graph.operation($crate::__graph_op!($op), [$($node),+]);
+        $graph.name($result, stringify!($result).to_string());
         $crate::graph! { @build_multi $graph, $($rest)* }
     };
 
     // Generic N-ary op with extra args: (@a, @b, @c) -> scale(2.0) -> @res
     (@build_multi $graph:ident, ( $( @ $node:ident ),+ ) -> $op:ident ( $($op_args:tt)* ) -> @ $result:ident $($rest:tt)*) => {
-        let $result = $graph.operation(Op::$op($($op_args)*), vec![$($node),+]);
+        let $result = $graph.operation($crate::__graph_op!($op($($op_args)*)), [$($node),+]);
+        $graph.name($result, stringify!($result).to_string());
+        $crate::graph! { @build_multi $graph, $($rest)* }
+    };
+
+    // Unnamed N-ary result going straight to an output: (@a, @b, @c) -> add -> output
+    (@build_multi $graph:ident, ( $( @ $node:ident ),+ ) -> $op:ident -> output $($rest:tt)*) => {
+        let result = $graph.operation($crate::__graph_op!($op), [$($node),+]);
+        $graph.output(result);
         $crate::graph! { @build_multi $graph, $($rest)* }
     };
 
-    (@build_multi $graph:ident, output @ $node:ident) => {
-        $graph.output($node);
+    (@build_multi $graph:ident, ( $( @ $node:ident ),+ ) -> $op:ident ( $($op_args:tt)* ) -> output $($rest:tt)*) => {
+        let result = $graph.operation($crate::__graph_op!($op($($op_args)*)), [$($node),+]);
+        $graph.output(result);
+        $crate::graph! { @build_multi $graph, $($rest)* }
+    };
+
+    // One `Node::Output` per listed node, returned by `compute` in this order
+    (@build_multi $graph:ident, output $( @ $node:ident ),+ $(,)?) => {
+        $( $graph.output($node); )+
         $graph
     };
 
     (@build_multi $graph:ident, output) => {
         $graph
     };
+
+    (@build_multi $graph:ident, ) => {
+        $graph
+    };
+}
+
+// Maps an op as written in `graph!` to its `Op`: variants by name (`Sin`, `Scale(2.0)`), or the
+// same names in lowercase
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __graph_op {
+    (scale ( $($args:tt)* )) => { $crate::autodiff::Op::Scale($($args)*) };
+    (sin) => { $crate::autodiff::Op::Sin };
+    (cos) => { $crate::autodiff::Op::Cos };
+    (abs) => { $crate::autodiff::Op::Abs };
+    (neg) => { $crate::autodiff::Op::Neg };
+    (recip) => { $crate::autodiff::Op::Recip };
+    (tanh) => { $crate::autodiff::Op::Tanh };
+    (exp) => { $crate::autodiff::Op::Exp };
+    (ln) => { $crate::autodiff::Op::Ln };
+    (sqrt) => { $crate::autodiff::Op::Sqrt };
+    (add) => { $crate::autodiff::Op::Add };
+    (mul) => { $crate::autodiff::Op::Mul };
+    (max) => { $crate::autodiff::Op::Max };
+    (min) => { $crate::autodiff::Op::Min };
+    ($op:ident) => { $crate::autodiff::Op::$op };
+    ($op:ident ( $($args:tt)* )) => { $crate::autodiff::Op::$op($($args)*) };
 }
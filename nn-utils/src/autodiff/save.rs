@@ -0,0 +1,281 @@
+//! Binary encoding of graphs, in the little-endian style of [`crate::network::write_header`]
+
+use super::{MultiGraph, Node, NodeId, Op};
+use std::{
+    collections::HashMap,
+    io::{self, Read, Write},
+};
+
+/// Identifies a saved [`MultiGraph`]
+pub const MAGIC: [u8; 4] = *b"nng1";
+
+impl MultiGraph {
+    /// Writes every node, then every name given by [`MultiGraph::input`] or [`MultiGraph::name`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::autodiff::MultiGraph;
+    /// use std::f64::consts::PI;
+    ///
+    /// let mut graph = nn_utils::graph! {
+    ///     inputs: [x, y]
+    ///     x -> Pow(2) -> @x_sq
+    ///     y -> Sin -> @y_sin
+    ///     (@x_sq, @y_sin) -> Add -> @result
+    ///     output @result
+    /// };
+    ///
+    /// let mut bytes = Vec::new();
+    /// graph.save(&mut bytes).expect("writing to a Vec cannot fail");
+    /// let mut restored = MultiGraph::load(&mut bytes.as_slice()).unwrap();
+    ///
+    /// let inputs = [2.0, PI / 2.0];
+    /// assert_eq!(restored.compute(&inputs), graph.compute(&inputs));
+    /// assert_eq!(restored.value_of("x_sq"), Some((4.0, 4.0)));
+    ///
+    /// // truncated input is an error rather than a partial graph
+    /// assert!(MultiGraph::load(&mut &bytes[..bytes.len() - 1]).is_err());
+    /// ```
+    ///
+    /// Corrupt lengths are errors too, without allocating what they claim:
+    /// ```rust
+    /// use nn_utils::autodiff::{MultiGraph, NodeId};
+    /// use std::io::ErrorKind;
+    ///
+    /// let mut graph = MultiGraph::new();
+    /// let x = graph.input("x".to_string());
+    /// graph.output(x);
+    ///
+    /// let mut bytes = Vec::new();
+    /// graph.save(&mut bytes).unwrap();
+    ///
+    /// // after the magic number: the node count, then the first node's tag and name length
+    /// let mut huge_count = bytes.clone();
+    /// huge_count[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+    /// assert!(MultiGraph::load(&mut huge_count.as_slice()).is_err());
+    ///
+    /// let mut huge_name = bytes.clone();
+    /// huge_name[9..13].copy_from_slice(&u32::MAX.to_le_bytes());
+    /// let err = MultiGraph::load(&mut huge_name.as_slice()).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::InvalidData);
+    ///
+    /// // and ids too large for the format aren't written at all
+    /// let mut far = MultiGraph::new();
+    /// far.output(NodeId::from_index(u32::MAX as usize + 1));
+    /// let err = far.save(&mut Vec::new()).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    /// ```
+    pub fn save(&self, w: &mut dyn Write) -> io::Result<()> {
+        w.write_all(&MAGIC)?;
+
+        write_u32(w, self.nodes.len())?;
+        for node in &self.nodes {
+            match node {
+                Node::Input(name) => {
+                    w.write_all(&[0])?;
+                    write_str(w, name)?;
+                }
+                Node::Const(value) => {
+                    w.write_all(&[1])?;
+                    w.write_all(&value.to_le_bytes())?;
+                }
+                Node::AfterOperation(op, ids) => {
+                    w.write_all(&[2])?;
+                    write_op(w, *op)?;
+                    write_u32(w, ids.len())?;
+                    for id in ids {
+                        write_u32(w, id.0)?;
+                    }
+                }
+                Node::Output(id) => {
+                    w.write_all(&[3])?;
+                    write_u32(w, id.0)?;
+                }
+            }
+        }
+
+        // sorted so that saving the same graph twice gives the same bytes
+        let mut names: Vec<_> = self.node_map.iter().collect();
+        names.sort_by_key(|&(name, id)| (id.0, name));
+
+        write_u32(w, names.len())?;
+        for (name, id) in names {
+            write_str(w, name)?;
+            write_u32(w, id.0)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a graph written by [`MultiGraph::save`], erroring if a node refers to a later one
+    pub fn load(r: &mut dyn Read) -> io::Result<Self> {
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(invalid(format!("bad magic number {magic:?}")));
+        }
+
+        // lengths come from the stream, so nothing is reserved up front: a corrupt count runs out
+        // of bytes instead of allocating gigabytes
+        let count = read_u32(r)?;
+        let mut nodes = Vec::new();
+
+        for i in 0..count {
+            // nodes may only refer to earlier ones, which keeps `compute` a single pass
+            let read_id = |r: &mut dyn Read| {
+                let id = read_u32(r)?;
+                if id < i {
+                    Ok(NodeId(id))
+                } else {
+                    Err(invalid(format!("node {i} refers to node {id}")))
+                }
+            };
+
+            let node = match read_u8(r)? {
+                0 => Node::Input(read_str(r)?),
+                1 => Node::Const(read_f64(r)?),
+                2 => {
+                    let op = read_op(r)?;
+                    let mut ids = Vec::new();
+                    for _ in 0..read_u32(r)? {
+                        ids.push(read_id(r)?);
+                    }
+                    Node::AfterOperation(op, ids.into_boxed_slice())
+                }
+                3 => Node::Output(read_id(r)?),
+                tag => return Err(invalid(format!("node {i}: unknown tag {tag}"))),
+            };
+            nodes.push(node);
+        }
+
+        let mut node_map = HashMap::new();
+        for _ in 0..read_u32(r)? {
+            let name = read_str(r)?;
+            let id = read_u32(r)?;
+            if id >= count {
+                return Err(invalid(format!("`{name}` names missing node {id}")));
+            }
+            node_map.insert(name, NodeId(id));
+        }
+
+        Ok(Self {
+            next_id: nodes.len(),
+            nodes,
+            node_map,
+            ..Self::new()
+        })
+    }
+}
+
+fn write_op(w: &mut dyn Write, op: Op) -> io::Result<()> {
+    let (tag, params): (u8, &[f64]) = match op {
+        Op::Scale(factor) => (0, &[factor]),
+        Op::Sin => (1, &[]),
+        Op::Cos => (2, &[]),
+        Op::Pow(exp) => {
+            w.write_all(&[3])?;
+            return w.write_all(&exp.to_le_bytes());
+        }
+        Op::Powf(exp) => (4, &[exp]),
+        Op::Abs => (5, &[]),
+        Op::Neg => (6, &[]),
+        Op::Recip => (7, &[]),
+        Op::Clamp { min, max } => (8, &[min, max]),
+        Op::Add => (9, &[]),
+        Op::Mul => (10, &[]),
+        Op::Max => (11, &[]),
+        Op::Min => (12, &[]),
+        Op::Tanh => (13, &[]),
+        Op::Exp => (14, &[]),
+        Op::Ln => (15, &[]),
+        Op::Sqrt => (16, &[]),
+    };
+
+    w.write_all(&[tag])?;
+    for p in params {
+        w.write_all(&p.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+fn read_op(r: &mut dyn Read) -> io::Result<Op> {
+    Ok(match read_u8(r)? {
+        0 => Op::Scale(read_f64(r)?),
+        1 => Op::Sin,
+        2 => Op::Cos,
+        3 => {
+            let mut bytes = [0; 4];
+            r.read_exact(&mut bytes)?;
+            Op::Pow(i32::from_le_bytes(bytes))
+        }
+        4 => Op::Powf(read_f64(r)?),
+        5 => Op::Abs,
+        6 => Op::Neg,
+        7 => Op::Recip,
+        8 => Op::Clamp {
+            min: read_f64(r)?,
+            max: read_f64(r)?,
+        },
+        9 => Op::Add,
+        10 => Op::Mul,
+        11 => Op::Max,
+        12 => Op::Min,
+        13 => Op::Tanh,
+        14 => Op::Exp,
+        15 => Op::Ln,
+        16 => Op::Sqrt,
+        tag => return Err(invalid(format!("unknown op tag {tag}"))),
+    })
+}
+
+fn invalid(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+// lengths and ids past `u32::MAX` are an error rather than silently wrapping into a corrupt file
+fn write_u32(w: &mut dyn Write, n: usize) -> io::Result<()> {
+    let n = u32::try_from(n).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{n} does not fit the format's 32-bit lengths"),
+        )
+    })?;
+    w.write_all(&n.to_le_bytes())
+}
+
+fn write_str(w: &mut dyn Write, s: &str) -> io::Result<()> {
+    write_u32(w, s.len())?;
+    w.write_all(s.as_bytes())
+}
+
+fn read_u8(r: &mut dyn Read) -> io::Result<u8> {
+    let mut byte = [0];
+    r.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn read_u32(r: &mut dyn Read) -> io::Result<usize> {
+    let mut bytes = [0; 4];
+    r.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes) as usize)
+}
+
+fn read_f64(r: &mut dyn Read) -> io::Result<f64> {
+    let mut bytes = [0; 8];
+    r.read_exact(&mut bytes)?;
+    Ok(f64::from_le_bytes(bytes))
+}
+
+fn read_str(r: &mut dyn Read) -> io::Result<String> {
+    let len = read_u32(r)?;
+    let mut bytes = Vec::new();
+    r.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(invalid(format!(
+            "expected a {len} byte string, found {} bytes",
+            bytes.len()
+        )));
+    }
+    String::from_utf8(bytes).map_err(|e| invalid(e.to_string()))
+}
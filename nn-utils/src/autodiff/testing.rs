@@ -0,0 +1,74 @@
+//! Helpers for checking hand-written derivatives against finite differences
+
+use super::MultiGraph;
+
+impl MultiGraph {
+    /// Central-difference estimate of the first output's partial derivative with respect to each
+    /// input, in declaration order
+    ///
+    /// [`MultiGraph::compute`] seeds every input with a unit tangent, so its tangent should match
+    /// the sum of these partials up to `O(eps²)` truncation error.
+    ///
+    /// Panics if the graph has no outputs, or like [`MultiGraph::compute`] if `inputs` doesn't
+    /// match the graph's inputs.
+    ///
+    /// # Examples
+    ///
+    /// Every op's analytic derivative agrees with the numerical one:
+    /// ```rust
+    /// use nn_utils::autodiff::{MultiGraph, Op};
+    ///
+    /// let unary = [
+    ///     Op::Scale(-1.5), Op::Sin, Op::Cos, Op::Pow(3), Op::Pow(-2), Op::Powf(0.5),
+    ///     Op::Abs, Op::Neg, Op::Recip, Op::Tanh, Op::Exp, Op::Ln, Op::Sqrt,
+    ///     Op::Clamp { min: -1.0, max: 1.0 },
+    ///     Op::Clamp { min: 1.0, max: 2.0 },
+    /// ];
+    /// let binary = [Op::Add, Op::Mul, Op::Max, Op::Min];
+    ///
+    /// for (op, inputs) in unary
+    ///     .map(|op| (op, vec![0.7]))
+    ///     .into_iter()
+    ///     // the other side of `Abs`'s kink
+    ///     .chain([(Op::Abs, vec![-0.7])])
+    ///     .chain(binary.map(|op| (op, vec![0.7, -1.3])))
+    /// {
+    ///     let mut graph = MultiGraph::new();
+    ///     let ids: Vec<_> = (0..inputs.len()).map(|i| graph.input(format!("x{i}"))).collect();
+    ///     let res = graph.operation(op, ids);
+    ///     graph.output(res);
+    ///
+    ///     let analytic = graph.compute(&inputs)[0].1;
+    ///     let numeric: f64 = graph.check_gradient(&inputs, 1e-6).iter().sum();
+    ///     assert!((analytic - numeric).abs() < 1e-6, "{op:?}: {analytic} vs {numeric}");
+    /// }
+    /// ```
+    ///
+    /// Per-input partials of `x * y + 3`:
+    /// ```rust
+    /// let mut graph = nn_utils::graph! {
+    ///     inputs: [x, y]
+    ///     (@x, @y) -> Mul -> @xy
+    ///     xy -> add_const(3.0) -> @res
+    ///     output @res
+    /// };
+    ///
+    /// let grad = graph.check_gradient(&[2.0, 5.0], 1e-6);
+    /// assert!((grad[0] - 5.0).abs() < 1e-6 && (grad[1] - 2.0).abs() < 1e-6);
+    /// ```
+    pub fn check_gradient(&mut self, inputs: &[f64], eps: f64) -> Vec<f64> {
+        let mut shifted = inputs.to_vec();
+
+        (0..inputs.len())
+            .map(|i| {
+                shifted[i] = inputs[i] + eps;
+                let plus = self.eval(&shifted)[0];
+                shifted[i] = inputs[i] - eps;
+                let minus = self.eval(&shifted)[0];
+                shifted[i] = inputs[i];
+
+                (plus - minus) / (2.0 * eps)
+            })
+            .collect()
+    }
+}
@@ -1,22 +1,92 @@
-use crate::tensor::Tensor;
-use std::{array, marker::PhantomData};
+use crate::{
+    network::{Parameters, read_f32s, write_f32s},
+    tensor::{ShapeDims, Tensor},
+};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use std::{
+    array,
+    io::{self, Read, Write},
+    marker::PhantomData,
+};
 
 // height, width, and depth (input channel size)
 // pub struct Filter<const H: usize, const W: usize, const D: usize>([[[f32; H]; W]; D]);
+//
+// with `G` groups, the filter only spans its group's `D / G` channels. The division lives here
+// rather than in `Conv` since passing `{ IC / G }` as `D` trips up the compiler
 #[derive(Debug, Clone)]
-pub struct Filter<const H: usize, const W: usize, const D: usize>(
-    Tensor<{ H * W * D }, 3, shape_ty!(H, W, D)>,
+pub struct Filter<const H: usize, const W: usize, const D: usize, const G: usize = 1>(
+    Tensor<{ H * W * (D / G) }, 3, shape_ty!(H, W, D / G), f32>,
 )
 where
-    Tensor<{ H * W * D }, 3, shape_ty!(H, W, D)>: Sized; // current limitation of compiler's const generic features
+    Tensor<{ H * W * (D / G) }, 3, shape_ty!(H, W, D / G), f32>: Sized; // current limitation of compiler's const generic features
 
-impl<const H: usize, const W: usize, const D: usize> Default for Filter<H, W, D>
+impl<const H: usize, const W: usize, const D: usize, const G: usize> Filter<H, W, D, G>
 where
-    Tensor<{ H * W * D }, 3, shape_ty!(H, W, D)>: Sized,
+    Tensor<{ H * W * (D / G) }, 3, shape_ty!(H, W, D / G), f32>: Sized,
 {
-    fn default() -> Self {
-        let mut arr = [0.; H * W * D];
-        rand::fill(&mut arr);
+    /// Wraps known weights, e.g. ones trained elsewhere
+    pub fn from_tensor(
+        weights: Tensor<{ H * W * (D / G) }, 3, shape_ty!(H, W, D / G), f32>,
+    ) -> Self {
+        Self(weights)
+    }
+
+    pub fn as_tensor(&self) -> &Tensor<{ H * W * (D / G) }, 3, shape_ty!(H, W, D / G), f32> {
+        &self.0
+    }
+
+    pub fn as_tensor_mut(
+        &mut self,
+    ) -> &mut Tensor<{ H * W * (D / G) }, 3, shape_ty!(H, W, D / G), f32> {
+        &mut self.0
+    }
+
+    /// He initialization: zero-mean normal weights with standard deviation `sqrt(2 / fan_in)`,
+    /// which keeps activations from growing or vanishing through ReLU layers
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::conv::Filter;
+    ///
+    /// let filter = Filter::<16, 16, 16>::he(16 * 16 * 16);
+    /// let (mut sum, mut sum_sq) = (0.0, 0.0);
+    /// for y in 0..16 {
+    ///     for x in 0..16 {
+    ///         for c in 0..16 {
+    ///             let w = *filter.as_tensor().at([y, x, c]) as f64;
+    ///             sum += w;
+    ///             sum_sq += w * w;
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let n = 4096.0;
+    /// let (mean, var) = (sum / n, sum_sq / n - (sum / n).powi(2));
+    /// let target = 2.0 / n;
+    /// assert!(mean.abs() < 0.1 * target.sqrt());
+    /// assert!((var - target).abs() < 0.1 * target);
+    /// ```
+    pub fn he(fan_in: usize) -> Self {
+        let std = (2.0 / fan_in as f32).sqrt();
+        let mut rng = rand::rng();
+
+        Self(Tensor {
+            data: Box::new(array::from_fn(|_| std * standard_normal(&mut rng))),
+            _shape_marker: PhantomData,
+        })
+    }
+
+    /// Like [`Filter::default`], but the same `seed` always gives the same weights
+    pub fn seeded(seed: u64) -> Self {
+        Self::from_rng(&mut StdRng::seed_from_u64(seed))
+    }
+
+    // uniform weights in [0, 1)
+    fn from_rng(rng: &mut impl Rng) -> Self {
+        let mut arr = [0.; H * W * (D / G)];
+        rng.fill(&mut arr);
 
         Self(Tensor {
             data: Box::new(arr),
@@ -25,6 +95,54 @@ where
     }
 }
 
+// Box-Muller transform, as `rand` alone has no normal distribution
+fn standard_normal(rng: &mut impl Rng) -> f32 {
+    // `1 - u` lies in (0, 1], keeping the logarithm finite
+    let u1 = 1.0 - rng.random::<f32>();
+    let u2 = rng.random::<f32>();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f32::consts::PI * u2).cos()
+}
+
+impl<const H: usize, const W: usize, const D: usize, const G: usize> Default for Filter<H, W, D, G>
+where
+    Tensor<{ H * W * (D / G) }, 3, shape_ty!(H, W, D / G), f32>: Sized,
+{
+    fn default() -> Self {
+        Self::from_rng(&mut rand::rng())
+    }
+}
+
+/// How a [`Conv`] reads input positions that fall in the padding
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PaddingMode {
+    /// Padding reads as zero
+    #[default]
+    Zero,
+    /// Mirror the input about its edge, without repeating the edge itself
+    Reflect,
+    /// Repeat the nearest edge element
+    Replicate,
+}
+
+impl PaddingMode {
+    // maps a coordinate along an axis of length `len` onto `0..len`, or `None` if it reads as zero
+    fn resolve(self, i: isize, len: usize) -> Option<usize> {
+        let last = len as isize - 1;
+        if (0..=last).contains(&i) {
+            return Some(i as usize);
+        }
+
+        match self {
+            PaddingMode::Zero => None,
+            // padding narrower than the input reflects at most once
+            PaddingMode::Reflect => {
+                Some(if i < 0 { -i } else { 2 * last - i }.clamp(0, last) as usize)
+            }
+            PaddingMode::Replicate => Some(i.clamp(0, last) as usize),
+        }
+    }
+}
+
 /// A convolutional layer
 ///
 /// `FH` - filter/kernel height
@@ -33,6 +151,54 @@ where
 /// `OC` - number of output channels (equivalently, number of kernels/filters)
 /// `S` - stride
 /// `P` - padding
+/// `G` - groups: output channel group `g` only sees input channel group `g`, so each filter is
+/// `IC / G` deep. `G == IC == OC` is a depthwise convolution
+///
+/// The filter has to fit the padded input, `FH <= IH + 2 * P` and `FW <= IW + 2 * P`, and the
+/// stride must be at least 1. Otherwise [`Conv::init`] fails to compile:
+/// ```rust,compile_fail
+/// // a 5x5 filter over an unpadded 3x3 input
+/// let conv = nn_utils::conv::Conv::<3, 3, 1, 5, 5, 1, 1, 0>::init();
+/// ```
+///
+/// ```rust
+/// // ...while padding by 1 makes it fit
+/// let conv = nn_utils::conv::Conv::<3, 3, 1, 5, 5, 1, 1, 1>::init();
+/// assert_eq!(conv.create_output_space().dims(), [1, 1, 1]);
+/// ```
+///
+/// # Examples
+///
+/// Two groups behave like two independent convolutions over half the channels each:
+/// ```rust
+/// use nn_utils::conv::Conv;
+///
+/// let grouped = Conv::<5, 5, 4, 3, 3, 2, 1, 0, 2>::init();
+/// let mut halves = [Conv::<5, 5, 2, 3, 3, 1, 1, 0>::init(), Conv::init()];
+/// for (half, filter) in halves.iter_mut().zip(grouped.filters()) {
+///     *half.filters_mut()[0].as_tensor_mut() = filter.as_tensor().clone();
+/// }
+///
+/// let data: [f32; 100] = std::array::from_fn(|i| (i as f32).sin());
+/// let mut output = grouped.create_output_space();
+/// grouped.forward(&grouped.input_from_data(data), &mut output);
+///
+/// for (g, half) in halves.iter().enumerate() {
+///     let input = half.input_from_data(data[g * 50..][..50].try_into().unwrap());
+///     let mut expected = half.create_output_space();
+///     half.forward(&input, &mut expected);
+///
+///     for y in 0..3 {
+///         for x in 0..3 {
+///             assert_eq!(*output.at([g, y, x]), *expected.at([0, y, x]));
+///         }
+///     }
+/// }
+///
+/// let mut fast = grouped.create_output_space();
+/// grouped.forward_im2col(&grouped.input_from_data(data), &mut fast);
+/// assert!(output.approx_eq(&fast, 0.0));
+/// ```
 #[derive(Debug)]
 pub struct Conv<
     const IW: usize,
@@ -43,10 +209,14 @@ pub struct Conv<
     const OC: usize,
     const S: usize,
     const P: usize,
+    const G: usize = 1,
 > where
-    Tensor<{ FH * FW * IC }, 3, shape_ty!(FH, FW, IC)>: Sized,
+    Tensor<{ FH * FW * (IC / G) }, 3, shape_ty!(FH, FW, IC / G), f32>: Sized,
 {
-    data: [Filter<FH, FW, IC>; OC],
+    data: [Filter<FH, FW, IC, G>; OC],
+    // accumulated by `backward`, one per filter
+    grads: [Filter<FH, FW, IC, G>; OC],
+    padding_mode: PaddingMode,
 }
 impl<
     const IW: usize,
@@ -57,13 +227,206 @@ impl<
     const OC: usize,
     const S: usize,
     const P: usize,
-> Conv<IW, IH, IC, FH, FW, OC, S, P>
+    const G: usize,
+> Conv<IW, IH, IC, FH, FW, OC, S, P, G>
 where
-    Tensor<{ FH * FW * IC }, 3, shape_ty!(FH, FW, IC)>: Sized,
+    Tensor<{ FH * FW * (IC / G) }, 3, shape_ty!(FH, FW, IC / G), f32>: Sized,
 {
+    // evaluated when `init` is monomorphized, so a bad configuration fails to compile
+    const VALID: () = {
+        assert!(S >= 1, "stride must be at least 1");
+        assert!(IH + 2 * P >= FH, "filter is taller than the padded input");
+        assert!(IW + 2 * P >= FW, "filter is wider than the padded input");
+    };
+
+    /// Output height, `(IH + 2 * P - FH) / S + 1`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::conv::Conv;
+    ///
+    /// type Strided = Conv<9, 7, 2, 3, 3, 4, 2, 1>;
+    /// assert_eq!((Strided::OUT_H, Strided::OUT_W, Strided::OUT_N), (4, 5, 4 * 4 * 5));
+    /// assert_eq!(Strided::init().create_output_space().dims(), [4, 4, 5]);
+    ///
+    /// type Wide = Conv<28, 28, 1, 5, 5, 6, 1, 0>;
+    /// assert_eq!((Wide::OUT_H, Wide::OUT_W, Wide::OUT_N), (24, 24, 6 * 24 * 24));
+    /// assert_eq!(Wide::init().create_output_space().len(), Wide::OUT_N);
+    /// ```
+    pub const OUT_H: usize = (IH + 2 * P - FH) / S + 1;
+    /// Output width, `(IW + 2 * P - FW) / S + 1`
+    pub const OUT_W: usize = (IW + 2 * P - FW) / S + 1;
+    /// Elements in the output, `OC * OUT_H * OUT_W`
+    pub const OUT_N: usize = OC * Self::OUT_H * Self::OUT_W;
+
     pub fn init() -> Self {
+        Self::with_filters(Filter::default)
+    }
+
+    // checks the configuration before drawing any filters, so every `init_*` only touches its
+    // own generator
+    fn with_filters(mut filter: impl FnMut() -> Filter<FH, FW, IC, G>) -> Self {
+        let () = Self::VALID;
+        assert!(
+            G > 0 && IC.is_multiple_of(G) && OC.is_multiple_of(G),
+            "{G} groups must divide both the {IC} input and {OC} output channels"
+        );
+
         Conv {
-            data: array::from_fn(|_| Filter::default()),
+            data: array::from_fn(|_| filter()),
+            grads: array::from_fn(|_| Filter(Tensor::zeros())),
+            padding_mode: PaddingMode::default(),
+        }
+    }
+
+    /// Like [`Conv::init`], but with [`Filter::he`] weights scaled to the `FH * FW * IC / G`
+    /// inputs each output sees
+    pub fn init_he() -> Self {
+        Self::with_filters(|| Filter::he(FH * FW * (IC / G)))
+    }
+
+    /// Like [`Conv::init`], but with filters drawn from a generator seeded with `seed`, so runs can
+    /// be reproduced
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::conv::Conv;
+    ///
+    /// type C = Conv<8, 8, 3, 3, 3, 4, 1, 0>;
+    /// let (a, b, c) = (C::init_seeded(42), C::init_seeded(42), C::init_seeded(7));
+    ///
+    /// let weights = |conv: &C| -> Vec<f32> {
+    ///     conv.filters().iter().flat_map(|f| f.as_tensor().as_slice().to_vec()).collect()
+    /// };
+    /// assert_eq!(weights(&a), weights(&b));
+    /// assert_ne!(weights(&a), weights(&c));
+    ///
+    /// // filters differ from each other, not just between seeds
+    /// assert_ne!(a.filters()[0].as_tensor().as_slice(), a.filters()[1].as_tensor().as_slice());
+    /// ```
+    pub fn init_seeded(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        Self::with_filters(|| Filter::from_rng(&mut rng))
+    }
+
+    /// # Examples
+    ///
+    /// The modes only differ where the kernel overlaps the padding:
+    /// ```rust
+    /// use nn_utils::{Tensor, conv::{Conv, Filter, PaddingMode}};
+    ///
+    /// let corner_and_center = |mode| {
+    ///     let mut conv = Conv::<3, 3, 1, 3, 3, 1, 1, 1>::init().with_padding_mode(mode);
+    ///     conv.filters_mut()[0] = Filter::from_tensor(Tensor::ones());
+    ///
+    ///     let input = conv.input_from_data(std::array::from_fn(|i| i as f32));
+    ///     let mut output = conv.create_output_space();
+    ///     conv.forward(&input, &mut output);
+    ///     (*output.at([0, 0, 0]), *output.at([0, 1, 1]))
+    /// };
+    ///
+    /// assert_eq!(corner_and_center(PaddingMode::Zero), (8., 36.));
+    /// assert_eq!(corner_and_center(PaddingMode::Replicate), (12., 36.));
+    /// assert_eq!(corner_and_center(PaddingMode::Reflect), (24., 36.));
+    /// ```
+    pub fn with_padding_mode(mut self, mode: PaddingMode) -> Self {
+        self.padding_mode = mode;
+        self
+    }
+
+    pub fn padding_mode(&self) -> PaddingMode {
+        self.padding_mode
+    }
+
+    /// One filter per output channel
+    pub fn filters(&self) -> &[Filter<FH, FW, IC, G>; OC] {
+        &self.data
+    }
+
+    /// # Examples
+    ///
+    /// Loading known weights:
+    /// ```rust
+    /// use nn_utils::{Tensor, conv::{Conv, Filter}};
+    ///
+    /// let mut conv = Conv::<3, 3, 1, 3, 3, 2, 1, 0>::init();
+    /// conv.filters_mut()[0] = Filter::from_tensor(Tensor::ones());
+    /// conv.filters_mut()[1] = Filter::from_tensor(Tensor::fill(-1.));
+    /// assert_eq!(*conv.filters()[1].as_tensor().at([2, 2, 0]), -1.);
+    ///
+    /// let input = conv.input_from_data(std::array::from_fn(|i| i as f32));
+    /// let mut output = conv.create_output_space();
+    /// conv.forward(&input, &mut output);
+    ///
+    /// // a 3x3 kernel over a 3x3 input covers every element once
+    /// assert_eq!(*output.at([0, 0, 0]), 36.);
+    /// assert_eq!(*output.at([1, 0, 0]), -36.);
+    /// ```
+    pub fn filters_mut(&mut self) -> &mut [Filter<FH, FW, IC, G>; OC] {
+        &mut self.data
+    }
+
+    /// Number of weights, `OC * FH * FW * IC / G` (there are no biases)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::conv::Conv;
+    ///
+    /// // 3 filters of 3x3x2 over a 5x5 input with padding 1, so a 5x5 output
+    /// let conv = Conv::<5, 5, 2, 3, 3, 3, 1, 1>::init();
+    /// assert_eq!(conv.num_params(), 3 * 3 * 3 * 2);
+    /// assert_eq!(conv.flops(), 3 * 5 * 5 * (3 * 3 * 2) * 2);
+    ///
+    /// // stride 2 halves (rounding up) each side of the output, and two groups halve the depth
+    /// let conv = Conv::<5, 5, 2, 3, 3, 4, 2, 1, 2>::init();
+    /// assert_eq!(conv.num_params(), 4 * 3 * 3 * 1);
+    /// assert_eq!(conv.flops(), 4 * 3 * 3 * (3 * 3 * 1) * 2);
+    /// ```
+    pub fn num_params(&self) -> usize {
+        OC * FH * FW * (IC / G)
+    }
+
+    /// Floating point operations in one [`Conv::forward`], counting a multiply-add as two
+    pub fn flops(&self) -> usize {
+        let (out_h, out_w) = (Self::OUT_H, Self::OUT_W);
+        OC * out_h * out_w * FH * FW * (IC / G) * 2
+    }
+
+    /// Sum of squares of every filter weight, for L2 regularization
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::{Tensor, conv::{Conv, Filter}};
+    ///
+    /// let mut conv = Conv::<4, 4, 1, 2, 2, 2, 1, 0>::init();
+    /// let [a, b] = conv.filters_mut();
+    /// a.as_tensor_mut().as_mut_slice().copy_from_slice(&[1., -2., 3., 0.5]);
+    /// *b = Filter::from_tensor(Tensor::fill(2.));
+    /// assert_eq!(conv.l2_penalty(), (1. + 4. + 9. + 0.25) + 4. * 4.);
+    ///
+    /// let mut grads = [Filter::from_tensor(Tensor::ones()), Filter::from_tensor(Tensor::zeros())];
+    /// conv.add_weight_decay_grad(&mut grads, 0.5);
+    /// assert_eq!(grads[0].as_tensor().as_slice(), &[1.5, 0., 2.5, 1.25]);
+    /// assert_eq!(grads[1].as_tensor().as_slice(), &[1.; 4]);
+    /// ```
+    pub fn l2_penalty(&self) -> f32 {
+        self.data
+            .iter()
+            .map(|f| f.as_tensor().dot(f.as_tensor()))
+            .sum()
+    }
+
+    /// Adds `lambda * weight` to each filter's gradient, the derivative of `lambda / 2 * l2_penalty()`
+    pub fn add_weight_decay_grad(&self, grads: &mut [Filter<FH, FW, IC, G>; OC], lambda: f32) {
+        for (grad, filter) in grads.iter_mut().zip(&self.data) {
+            let weights = filter.as_tensor().as_slice();
+            for (g, w) in grad.as_tensor_mut().as_mut_slice().iter_mut().zip(weights) {
+                *g += lambda * w;
+            }
         }
     }
 
@@ -73,31 +436,70 @@ where
         { OC * ((IH + 2 * P - FH) / S + 1) * ((IW + 2 * P - FW) / S + 1) },
         3,
         shape_ty!(OC, (IH + 2 * P - FH) / S + 1, (IW + 2 * P - FW) / S + 1),
+        f32,
     > {
         Tensor::new()
     }
 
     pub fn input_from_data(
         &self,
-        data: [f64; IC * IH * IW],
-    ) -> Tensor<{ IC * IH * IW }, 3, shape_ty!(IC, IH, IW)> {
-        Tensor::from(data).reshape()
+        data: [f32; IC * IH * IW],
+    ) -> Tensor<{ IC * IH * IW }, 3, shape_ty!(IC, IH, IW), f32> {
+        Tensor::from(data)
+            .try_reshape()
+            .expect("IC * IH * IW elements always fill (IC, IH, IW)")
     }
 
+    /// Convolves `input`, overwriting `output`
+    ///
+    /// `output` should come from [`Conv::create_output_space`] on this same layer. Debug builds
+    /// check that it holds [`Conv::OUT_N`] elements.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::{Tensor, conv::Conv};
+    ///
+    /// let conv = Conv::<6, 4, 2, 3, 3, 5, 1, 0>::init();
+    /// let mut output = conv.create_output_space();
+    /// conv.forward(&Tensor::random(), &mut output);
+    /// assert_eq!(output.dims(), [5, 2, 4]);
+    /// ```
     pub fn forward(
         &self,
-        input: &Tensor<{ IC * IH * IW }, 3, shape_ty!(IC, IH, IW)>,
+        input: &Tensor<{ IC * IH * IW }, 3, shape_ty!(IC, IH, IW), f32>,
         output: &mut Tensor<
             { OC * ((IH + 2 * P - FH) / S + 1) * ((IW + 2 * P - FW) / S + 1) },
             3,
             shape_ty!(OC, (IH + 2 * P - FH) / S + 1, (IW + 2 * P - FW) / S + 1),
+            f32,
         >,
     ) {
-        let out_h = (IH + 2 * P - FH) / S + 1;
-        let out_w = (IW + 2 * P - FW) / S + 1;
+        debug_assert_eq!(
+            output.len(),
+            Self::OUT_N,
+            "output is not this layer's shape"
+        );
+        self.forward_slice(input.as_slice(), output.as_mut_slice());
+    }
+
+    /// [`Conv::forward`] on flat buffers, laid out `(IC, IH, IW)` and `(OC, OUT_H, OUT_W)`
+    ///
+    /// This is what `network!` calls, as its activations live in plain `f32` buffers. Panics if
+    /// `input` or `output` is shorter than the layer's input or output.
+    pub fn forward_slice(&self, input: &[f32], output: &mut [f32]) {
+        let (input, output) = (&input[..IC * IH * IW], &mut output[..Self::OUT_N]);
+        let (out_h, out_w) = (Self::OUT_H, Self::OUT_W);
+
+        // row-major strides of the input (IC, IH, IW), filter (FH, FW, IC / G), and output (OC, out_h, out_w)
+        let in_strides = <shape_ty!(IC, IH, IW) as ShapeDims<3>>::strides();
+        let filter_strides = <shape_ty!(FH, FW, IC / G) as ShapeDims<3>>::strides();
+        let out_strides = [out_h * out_w, out_w, 1];
 
         for oc in 0..OC {
-            let filter = &self.data[oc].0; // Filter is Tensor<..., shape_ty!(FH, FW, IC)>
+            let filter = &self.data[oc].0.data; // Filter is Tensor<..., shape_ty!(FH, FW, IC / G)>
+            // first input channel of this output channel's group
+            let ic_base = oc / (OC / G) * (IC / G);
 
             for y in 0..out_h {
                 for x in 0..out_w {
@@ -106,21 +508,22 @@ where
                     // apply filter
                     for ky in 0..FH {
                         for kx in 0..FW {
-                            for ic in 0..IC {
+                            for ic in 0..IC / G {
                                 // calculate input position (accounting for stride)
                                 let in_y = (y * S + ky) as isize - P as isize;
                                 let in_x = (x * S + kx) as isize - P as isize;
 
-                                // check if within valid input bounds (zero padding outside)
-                                if in_y >= 0
-                                    && in_y < IH as isize
-                                    && in_x >= 0
-                                    && in_x < IW as isize
-                                {
-                                    // Input shape: (IC, IH, IW) -> index as [ic, y, x]
-                                    let input_val = input.at([ic, in_y as usize, in_x as usize]);
-                                    // Filter shape: (FH, FW, IC) -> index as [ky, kx, ic]
-                                    let filter_val = filter.at([ky, kx, ic]);
+                                // map into the input according to the padding mode (skipped if zero)
+                                if let (Some(in_y), Some(in_x)) = (
+                                    self.padding_mode.resolve(in_y, IH),
+                                    self.padding_mode.resolve(in_x, IW),
+                                ) {
+                                    let input_val = input[(ic_base + ic) * in_strides[0]
+                                        + in_y * in_strides[1]
+                                        + in_x * in_strides[2]];
+                                    let filter_val = filter[ky * filter_strides[0]
+                                        + kx * filter_strides[1]
+                                        + ic * filter_strides[2]];
 
                                     sum += filter_val * input_val;
                                 }
@@ -128,8 +531,501 @@ where
                         }
                     }
 
-                    // Output shape: (OC, out_h, out_w) -> index as [oc, y, x]
-                    output.set([oc, y, x], sum);
+                    output[oc * out_strides[0] + y * out_strides[1] + x * out_strides[2]] = sum;
+                }
+            }
+        }
+    }
+
+    /// [`Conv::forward`] followed by adding `bias[oc]` to every position of output channel `oc`
+    ///
+    /// The bias stays with the caller, e.g. for weights exported with a separate bias tensor.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::{Tensor, conv::Conv};
+    ///
+    /// let conv = Conv::<5, 5, 2, 3, 3, 3, 1, 0>::init();
+    /// let input = Tensor::random();
+    /// let bias = [0.5, -1.0, 2.0];
+    ///
+    /// let mut biased = conv.create_output_space();
+    /// conv.forward_with_bias(&input, &bias, &mut biased);
+    ///
+    /// let mut expected = conv.create_output_space();
+    /// conv.forward(&input, &mut expected);
+    /// // each output channel is a 3x3 plane
+    /// for (i, v) in expected.as_mut_slice().iter_mut().enumerate() {
+    ///     *v += bias[i / 9];
+    /// }
+    /// assert!(biased.approx_eq(&expected, 0.0));
+    /// ```
+    pub fn forward_with_bias(
+        &self,
+        input: &Tensor<{ IC * IH * IW }, 3, shape_ty!(IC, IH, IW), f32>,
+        bias: &[f32; OC],
+        output: &mut Tensor<
+            { OC * ((IH + 2 * P - FH) / S + 1) * ((IW + 2 * P - FW) / S + 1) },
+            3,
+            shape_ty!(OC, (IH + 2 * P - FH) / S + 1, (IW + 2 * P - FW) / S + 1),
+            f32,
+        >,
+    ) {
+        self.forward(input, output);
+
+        let plane = Self::OUT_H * Self::OUT_W;
+        for (channel, b) in output.as_mut_slice().chunks_exact_mut(plane).zip(bias) {
+            for v in channel {
+                *v += b;
+            }
+        }
+    }
+
+    /// Backpropagates `grad_output` (the loss gradient w.r.t. the output of [`Conv::forward`] on
+    /// `input`) through the layer
+    ///
+    /// Writes the gradient w.r.t. `input` into `grad_input` and accumulates the filter gradients,
+    /// which are kept until [`Conv::zero_grads`].
+    ///
+    /// # Examples
+    ///
+    /// For `loss = sum(output * weights)`, the gradients agree with finite differences and add up
+    /// over calls:
+    /// ```rust
+    /// use nn_utils::{Tensor, conv::{Conv, PaddingMode}};
+    ///
+    /// let mut conv = Conv::<4, 4, 2, 3, 3, 2, 1, 1>::init().with_padding_mode(PaddingMode::Reflect);
+    /// let input = conv.input_from_data(std::array::from_fn(|i| (i as f32).sin()));
+    /// let mut weights = conv.create_output_space();
+    /// for (i, w) in weights.as_mut_slice().iter_mut().enumerate() {
+    ///     *w = (i as f32 * 0.3).cos();
+    /// }
+    ///
+    /// let loss = |conv: &Conv<4, 4, 2, 3, 3, 2, 1, 1>, input: &Tensor<32, 3, _, f32>| {
+    ///     let mut output = conv.create_output_space();
+    ///     conv.forward(input, &mut output);
+    ///     output.dot(&weights)
+    /// };
+    ///
+    /// let mut grad_input = Tensor::zeros();
+    /// conv.backward(&input, &weights, &mut grad_input);
+    ///
+    /// let h = 1e-2;
+    /// for i in [0, 7, 20, 31] {
+    ///     let (mut lo, mut hi) = (input.clone(), input.clone());
+    ///     lo.as_mut_slice()[i] -= h;
+    ///     hi.as_mut_slice()[i] += h;
+    ///     let numeric = (loss(&conv, &hi) - loss(&conv, &lo)) / (2.0 * h);
+    ///     assert!((numeric - grad_input.as_slice()[i]).abs() < 1e-2);
+    /// }
+    ///
+    /// let nudged = |oc: usize, k: usize, d: f32| {
+    ///     let mut c = Conv::<4, 4, 2, 3, 3, 2, 1, 1>::init().with_padding_mode(PaddingMode::Reflect);
+    ///     for (dst, src) in c.filters_mut().iter_mut().zip(conv.filters()) {
+    ///         dst.as_tensor_mut().as_mut_slice().copy_from_slice(src.as_tensor().as_slice());
+    ///     }
+    ///     c.filters_mut()[oc].as_tensor_mut().as_mut_slice()[k] += d;
+    ///     c
+    /// };
+    /// for (oc, k) in [(0, 0), (1, 10), (1, 17)] {
+    ///     let numeric = (loss(&nudged(oc, k, h), &input) - loss(&nudged(oc, k, -h), &input)) / (2.0 * h);
+    ///     assert!((numeric - conv.grads()[oc].as_tensor().as_slice()[k]).abs() < 1e-2);
+    /// }
+    ///
+    /// // a second call adds to the filter gradients
+    /// let once = conv.grads()[1].as_tensor().clone();
+    /// conv.backward(&input, &weights, &mut grad_input);
+    /// for (twice, once) in conv.grads()[1].as_tensor().as_slice().iter().zip(once.as_slice()) {
+    ///     assert!((twice - 2.0 * once).abs() < 1e-5);
+    /// }
+    ///
+    /// conv.zero_grads();
+    /// assert!(conv.grads_as_slices_mut().iter().all(|g| g.iter().all(|&g| g == 0.0)));
+    /// ```
+    pub fn backward(
+        &mut self,
+        input: &Tensor<{ IC * IH * IW }, 3, shape_ty!(IC, IH, IW), f32>,
+        grad_output: &Tensor<
+            { OC * ((IH + 2 * P - FH) / S + 1) * ((IW + 2 * P - FW) / S + 1) },
+            3,
+            shape_ty!(OC, (IH + 2 * P - FH) / S + 1, (IW + 2 * P - FW) / S + 1),
+            f32,
+        >,
+        grad_input: &mut Tensor<{ IC * IH * IW }, 3, shape_ty!(IC, IH, IW), f32>,
+    ) {
+        let (out_h, out_w) = (Self::OUT_H, Self::OUT_W);
+
+        // same layouts as in `forward`
+        let in_strides = <shape_ty!(IC, IH, IW) as ShapeDims<3>>::strides();
+        let filter_strides = <shape_ty!(FH, FW, IC / G) as ShapeDims<3>>::strides();
+        let out_strides = [out_h * out_w, out_w, 1];
+
+        grad_input.data.fill(0.0);
+
+        for oc in 0..OC {
+            let filter = &self.data[oc].0.data;
+            let filter_grad = &mut self.grads[oc].0.data;
+            let ic_base = oc / (OC / G) * (IC / G);
+
+            for y in 0..out_h {
+                for x in 0..out_w {
+                    let go = grad_output.data
+                        [oc * out_strides[0] + y * out_strides[1] + x * out_strides[2]];
+
+                    for ky in 0..FH {
+                        for kx in 0..FW {
+                            let in_y = (y * S + ky) as isize - P as isize;
+                            let in_x = (x * S + kx) as isize - P as isize;
+
+                            // padded positions that read the input send their gradient back to it
+                            let (Some(in_y), Some(in_x)) = (
+                                self.padding_mode.resolve(in_y, IH),
+                                self.padding_mode.resolve(in_x, IW),
+                            ) else {
+                                continue;
+                            };
+
+                            for ic in 0..IC / G {
+                                let in_idx = (ic_base + ic) * in_strides[0]
+                                    + in_y * in_strides[1]
+                                    + in_x * in_strides[2];
+                                let f_idx = ky * filter_strides[0]
+                                    + kx * filter_strides[1]
+                                    + ic * filter_strides[2];
+
+                                filter_grad[f_idx] += go * input.data[in_idx];
+                                grad_input.data[in_idx] += go * filter[f_idx];
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Filter gradients accumulated by [`Conv::backward`], shaped like [`Conv::filters`]
+    pub fn grads(&self) -> &[Filter<FH, FW, IC, G>; OC] {
+        &self.grads
+    }
+
+    /// Each filter's gradient as a flat slice, in the order of [`Filter::as_tensor`]'s elements,
+    /// for optimizers that work on plain buffers
+    pub fn grads_as_slices_mut(&mut self) -> Vec<&mut [f32]> {
+        self.grads
+            .iter_mut()
+            .map(|g| g.as_tensor_mut().as_mut_slice())
+            .collect()
+    }
+
+    pub fn zero_grads(&mut self) {
+        for g in &mut self.grads {
+            g.as_tensor_mut().as_mut_slice().fill(0.0);
+        }
+    }
+
+    /// Runs [`Conv::forward`] on every image in `input`, writing each result to the matching slot of
+    /// `output`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::{Tensor, conv::Conv};
+    ///
+    /// let conv = Conv::<6, 6, 2, 3, 3, 4, 1, 0>::init();
+    /// let inputs = [Tensor::random(), Tensor::random(), Tensor::random()];
+    ///
+    /// let mut outputs = [(); 3].map(|_| conv.create_output_space());
+    /// conv.forward_batch(&inputs, &mut outputs);
+    ///
+    /// for (input, batched) in inputs.iter().zip(&outputs) {
+    ///     let mut single = conv.create_output_space();
+    ///     conv.forward(input, &mut single);
+    ///     assert!(batched.approx_eq(&single, 0.0));
+    /// }
+    /// ```
+    pub fn forward_batch(
+        &self,
+        input: &[Tensor<{ IC * IH * IW }, 3, shape_ty!(IC, IH, IW), f32>],
+        output: &mut [Tensor<
+            { OC * ((IH + 2 * P - FH) / S + 1) * ((IW + 2 * P - FW) / S + 1) },
+            3,
+            shape_ty!(OC, (IH + 2 * P - FH) / S + 1, (IW + 2 * P - FW) / S + 1),
+            f32,
+        >],
+    ) {
+        assert_eq!(
+            input.len(),
+            output.len(),
+            "batch of {} inputs needs as many output slots, got {}",
+            input.len(),
+            output.len()
+        );
+
+        for (image, out) in input.iter().zip(output) {
+            self.forward(image, out);
+        }
+    }
+
+    /// Unfolds every receptive field of `input` into one row of a matrix
+    ///
+    /// Row `y * out_w + x` holds the patch under output position `(y, x)`, laid out like a filter
+    /// (`FH`, `FW`, `IC`), with the padding filled in per [`PaddingMode`]. Convolution then
+    /// becomes a product of this matrix with the flattened filters; see [`Conv::forward_im2col`].
+    pub fn im2col(
+        &self,
+        input: &Tensor<{ IC * IH * IW }, 3, shape_ty!(IC, IH, IW), f32>,
+    ) -> Tensor<
+        { ((IH + 2 * P - FH) / S + 1) * ((IW + 2 * P - FW) / S + 1) * FH * FW * IC },
+        2,
+        shape_ty!(
+            ((IH + 2 * P - FH) / S + 1) * ((IW + 2 * P - FW) / S + 1),
+            FH * FW * IC
+        ),
+        f32,
+    > {
+        let (out_h, out_w) = (Self::OUT_H, Self::OUT_W);
+        let in_strides = <shape_ty!(IC, IH, IW) as ShapeDims<3>>::strides();
+
+        let mut cols = Tensor::new();
+        let mut rows = cols.data.chunks_exact_mut(FH * FW * IC);
+
+        for y in 0..out_h {
+            for x in 0..out_w {
+                let row = rows.next().expect("one row per output position");
+
+                for ky in 0..FH {
+                    for kx in 0..FW {
+                        let in_y = (y * S + ky) as isize - P as isize;
+                        let in_x = (x * S + kx) as isize - P as isize;
+
+                        // zero padding leaves the entry as is (already zero from `Tensor::new`)
+                        let (Some(in_y), Some(in_x)) = (
+                            self.padding_mode.resolve(in_y, IH),
+                            self.padding_mode.resolve(in_x, IW),
+                        ) else {
+                            continue;
+                        };
+
+                        for ic in 0..IC {
+                            row[(ky * FW + kx) * IC + ic] = input.data
+                                [ic * in_strides[0] + in_y * in_strides[1] + in_x * in_strides[2]];
+                        }
+                    }
+                }
+            }
+        }
+
+        cols
+    }
+
+    /// Same result as [`Conv::forward`], computed as a matrix product over [`Conv::im2col`]
+    ///
+    /// The summation order matches `forward`, so the outputs are bit-for-bit identical.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::{Tensor, conv::Conv};
+    ///
+    /// let conv = Conv::<8, 8, 3, 3, 3, 4, 1, 1>::init();
+    /// let input = Tensor::random();
+    ///
+    /// let (mut naive, mut fast) = (conv.create_output_space(), conv.create_output_space());
+    /// conv.forward(&input, &mut naive);
+    /// conv.forward_im2col(&input, &mut fast);
+    ///
+    /// assert!(naive.approx_eq(&fast, 0.0));
+    /// ```
+    pub fn forward_im2col(
+        &self,
+        input: &Tensor<{ IC * IH * IW }, 3, shape_ty!(IC, IH, IW), f32>,
+        output: &mut Tensor<
+            { OC * ((IH + 2 * P - FH) / S + 1) * ((IW + 2 * P - FW) / S + 1) },
+            3,
+            shape_ty!(OC, (IH + 2 * P - FH) / S + 1, (IW + 2 * P - FW) / S + 1),
+            f32,
+        >,
+    ) where
+        Tensor<
+            { ((IH + 2 * P - FH) / S + 1) * ((IW + 2 * P - FW) / S + 1) * FH * FW * IC },
+            2,
+            shape_ty!(
+                ((IH + 2 * P - FH) / S + 1) * ((IW + 2 * P - FW) / S + 1),
+                FH * FW * IC
+            ),
+            f32,
+        >: Sized,
+    {
+        let positions = ((IH + 2 * P - FH) / S + 1) * ((IW + 2 * P - FW) / S + 1);
+        let cols = self.im2col(input);
+
+        // (OC, K) x (K, positions), with the patches stored as rows of `cols`. Each patch holds all
+        // `IC` channels per kernel position, of which a group only reads its own `IC / G`
+        for (oc, out) in output.data.chunks_exact_mut(positions).enumerate() {
+            let filter = &self.data[oc].0.data[..];
+            let ic_base = oc / (OC / G) * (IC / G);
+
+            for (o, patch) in out.iter_mut().zip(cols.data.chunks_exact(FH * FW * IC)) {
+                let mut sum = 0.0;
+                for (f, x) in filter.chunks_exact(IC / G).zip(patch.chunks_exact(IC)) {
+                    for (f, x) in f.iter().zip(&x[ic_base..]) {
+                        sum += f * x;
+                    }
+                }
+                *o = sum;
+            }
+        }
+    }
+}
+
+/// A transposed convolutional layer, for upsampling
+///
+/// Each input pixel scatters its value times a filter into the output, making this the adjoint of
+/// a [`Conv`] with the same filters. Parameters mean the same as for [`Conv`], and the output is
+/// `(IH - 1) * S - 2 * P + FH` by `(IW - 1) * S - 2 * P + FW`.
+///
+/// # Examples
+///
+/// `<conv(x), y> == <x, conv_t(y)>`:
+/// ```rust
+/// use nn_utils::{Tensor, conv::{Conv, ConvTranspose}};
+///
+/// let conv = Conv::<5, 5, 2, 3, 3, 3, 2, 1>::init();
+/// let mut conv_t = ConvTranspose::<3, 3, 3, 3, 3, 2, 2, 1>::init();
+/// for (t, filter) in conv_t.filters_mut().iter_mut().zip(conv.filters()) {
+///     *t.as_tensor_mut() = filter.as_tensor().clone();
+/// }
+///
+/// let (x, y) = (Tensor::random(), Tensor::random());
+/// let (mut conv_x, mut conv_t_y) = (conv.create_output_space(), conv_t.create_output_space());
+/// conv.forward(&x, &mut conv_x);
+/// conv_t.forward(&y, &mut conv_t_y);
+///
+/// let (mut lhs, mut rhs) = (0., 0.);
+/// for c in 0..3 {
+///     for i in 0..3 {
+///         for j in 0..3 {
+///             lhs += conv_x.at([c, i, j]) * y.at([c, i, j]);
+///         }
+///     }
+/// }
+/// for c in 0..2 {
+///     for i in 0..5 {
+///         for j in 0..5 {
+///             rhs += x.at([c, i, j]) * conv_t_y.at([c, i, j]);
+///         }
+///     }
+/// }
+/// assert!((lhs - rhs).abs() < 1e-4);
+/// ```
+#[allow(clippy::identity_op)]
+#[derive(Debug)]
+pub struct ConvTranspose<
+    const IW: usize,
+    const IH: usize,
+    const IC: usize,
+    const FH: usize,
+    const FW: usize,
+    const OC: usize,
+    const S: usize,
+    const P: usize,
+> where
+    // `Filter`'s bound with a single group, spelled out so it matches
+    Tensor<{ FH * FW * (OC / 1) }, 3, shape_ty!(FH, FW, OC / 1), f32>: Sized,
+{
+    // one filter per input channel, spanning the output channels
+    data: [Filter<FH, FW, OC>; IC],
+}
+
+#[allow(clippy::identity_op)]
+impl<
+    const IW: usize,
+    const IH: usize,
+    const IC: usize,
+    const FH: usize,
+    const FW: usize,
+    const OC: usize,
+    const S: usize,
+    const P: usize,
+> ConvTranspose<IW, IH, IC, FH, FW, OC, S, P>
+where
+    // `Filter`'s bound with a single group, spelled out so it matches
+    Tensor<{ FH * FW * (OC / 1) }, 3, shape_ty!(FH, FW, OC / 1), f32>: Sized,
+{
+    pub fn init() -> Self {
+        ConvTranspose {
+            data: array::from_fn(|_| Filter::default()),
+        }
+    }
+
+    /// One filter per input channel, laid out (`FH`, `FW`, `OC`)
+    pub fn filters(&self) -> &[Filter<FH, FW, OC>; IC] {
+        &self.data
+    }
+
+    pub fn filters_mut(&mut self) -> &mut [Filter<FH, FW, OC>; IC] {
+        &mut self.data
+    }
+
+    pub fn create_output_space(
+        &self,
+    ) -> Tensor<
+        { OC * ((IH - 1) * S + FH - 2 * P) * ((IW - 1) * S + FW - 2 * P) },
+        3,
+        shape_ty!(OC, (IH - 1) * S + FH - 2 * P, (IW - 1) * S + FW - 2 * P),
+        f32,
+    > {
+        Tensor::new()
+    }
+
+    pub fn forward(
+        &self,
+        input: &Tensor<{ IC * IH * IW }, 3, shape_ty!(IC, IH, IW), f32>,
+        output: &mut Tensor<
+            { OC * ((IH - 1) * S + FH - 2 * P) * ((IW - 1) * S + FW - 2 * P) },
+            3,
+            shape_ty!(OC, (IH - 1) * S + FH - 2 * P, (IW - 1) * S + FW - 2 * P),
+            f32,
+        >,
+    ) {
+        let out_h = (IH - 1) * S + FH - 2 * P;
+        let out_w = (IW - 1) * S + FW - 2 * P;
+
+        let in_strides = <shape_ty!(IC, IH, IW) as ShapeDims<3>>::strides();
+        let out_strides = [out_h * out_w, out_w, 1];
+
+        output.data.fill(0.0);
+
+        for ic in 0..IC {
+            let filter = &self.data[ic].0.data;
+
+            for y in 0..IH {
+                for x in 0..IW {
+                    let input_val = input.data[ic * in_strides[0] + y * in_strides[1] + x];
+
+                    for ky in 0..FH {
+                        for kx in 0..FW {
+                            // the output position this input pixel reaches through (ky, kx),
+                            // dropping whatever lands in the padding
+                            let out_y = (y * S + ky) as isize - P as isize;
+                            let out_x = (x * S + kx) as isize - P as isize;
+                            if out_y < 0
+                                || out_y >= out_h as isize
+                                || out_x < 0
+                                || out_x >= out_w as isize
+                            {
+                                continue;
+                            }
+
+                            for oc in 0..OC {
+                                output.data[oc * out_strides[0]
+                                    + out_y as usize * out_strides[1]
+                                    + out_x as usize] +=
+                                    filter[(ky * FW + kx) * OC + oc] * input_val;
+                            }
+                        }
+                    }
                 }
             }
         }
@@ -154,24 +1050,198 @@ impl<
     const OC: usize,
     const S: usize,
     const P: usize,
-> ConvIO for Conv<IW, IH, IC, FH, FW, OC, S, P>
+    const G: usize,
+> ConvIO for Conv<IW, IH, IC, FH, FW, OC, S, P, G>
 where
-    Tensor<{ IC * IH * IW }, 3, shape_ty!(IC, IH, IW)>: Sized,
-    Tensor<{ FH * FW * IC }, 3, shape_ty!(FH, FW, IC)>: Sized,
+    Tensor<{ IC * IH * IW }, 3, shape_ty!(IC, IH, IW), f32>: Sized,
+    Tensor<{ FH * FW * (IC / G) }, 3, shape_ty!(FH, FW, IC / G), f32>: Sized,
     Tensor<
         { OC * ((IH + 2 * P - FH) / S + 1) * ((IW + 2 * P - FW) / S + 1) },
         3,
         shape_ty!(OC, (IH + 2 * P - FH) / S + 1, (IW + 2 * P - FW) / S + 1),
+        f32,
     >: Sized,
 {
     const N: usize = IC * IH * IW;
-    type Input = Tensor<{ IC * IH * IW }, 3, shape_ty!(IC, IH, IW)>;
+    type Input = Tensor<{ IC * IH * IW }, 3, shape_ty!(IC, IH, IW), f32>;
     type Output = Tensor<
         { OC * ((IH + 2 * P - FH) / S + 1) * ((IW + 2 * P - FW) / S + 1) },
         3,
         Self::OutputShape,
+        f32,
     >;
     type InputShape = shape_ty!(IC, IH, IW);
     type OutputShape = shape_ty!(OC, (IH + 2 * P - FH) / S + 1, (IW + 2 * P - FW) / S + 1);
     type FilterShape = shape_ty!(IC, FH, FW);
 }
+
+// the filters, one after another; accumulated gradients aren't saved
+impl<
+    const IW: usize,
+    const IH: usize,
+    const IC: usize,
+    const FH: usize,
+    const FW: usize,
+    const OC: usize,
+    const S: usize,
+    const P: usize,
+    const G: usize,
+> Parameters for Conv<IW, IH, IC, FH, FW, OC, S, P, G>
+where
+    Tensor<{ FH * FW * (IC / G) }, 3, shape_ty!(FH, FW, IC / G), f32>: Sized,
+{
+    fn save(&self, w: &mut dyn Write) -> io::Result<()> {
+        for filter in &self.data {
+            write_f32s(w, filter.0.as_slice())?;
+        }
+        Ok(())
+    }
+
+    fn load(&mut self, r: &mut dyn Read) -> io::Result<()> {
+        for filter in &mut self.data {
+            read_f32s(r, filter.0.as_mut_slice())?;
+        }
+        Ok(())
+    }
+}
+
+/// A convolutional layer over a single spatial axis, e.g. for time series
+///
+/// `IL` - input length
+/// `IC` - number of input channels
+/// `K` - kernel width
+/// `OC` - number of output channels (equivalently, number of kernels)
+/// `S` - stride
+/// `P` - zero padding at each end
+///
+/// # Examples
+///
+/// ```rust
+/// use nn_utils::{Tensor, conv::Conv1D};
+///
+/// let mut conv = Conv1D::<8, 1, 3, 1, 1, 0>::init();
+/// conv.filters_mut()[0] = Tensor::from([1., 0., -1.]).reshape();
+///
+/// // x[i] - x[i + 2] for x[i] = i^2
+/// let input = conv.input_from_data(std::array::from_fn(|i| (i * i) as f32));
+/// let mut output = conv.create_output_space();
+/// conv.forward(&input, &mut output);
+///
+/// for i in 0..6 {
+///     assert_eq!(*output.at([0, i]), -4. * i as f32 - 4.);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Conv1D<
+    const IL: usize,
+    const IC: usize,
+    const K: usize,
+    const OC: usize,
+    const S: usize,
+    const P: usize,
+> where
+    Tensor<{ K * IC }, 2, shape_ty!(K, IC), f32>: Sized,
+{
+    // like `Conv`'s filters with the height axis dropped: (K, IC)
+    data: [Tensor<{ K * IC }, 2, shape_ty!(K, IC), f32>; OC],
+}
+
+impl<
+    const IL: usize,
+    const IC: usize,
+    const K: usize,
+    const OC: usize,
+    const S: usize,
+    const P: usize,
+> Conv1D<IL, IC, K, OC, S, P>
+where
+    Tensor<{ K * IC }, 2, shape_ty!(K, IC), f32>: Sized,
+{
+    pub fn init() -> Self {
+        Conv1D {
+            data: array::from_fn(|_| Tensor::random()),
+        }
+    }
+
+    /// One `(K, IC)` filter per output channel
+    pub fn filters(&self) -> &[Tensor<{ K * IC }, 2, shape_ty!(K, IC), f32>; OC] {
+        &self.data
+    }
+
+    pub fn filters_mut(&mut self) -> &mut [Tensor<{ K * IC }, 2, shape_ty!(K, IC), f32>; OC] {
+        &mut self.data
+    }
+
+    pub fn create_output_space(
+        &self,
+    ) -> Tensor<{ OC * ((IL + 2 * P - K) / S + 1) }, 2, shape_ty!(OC, (IL + 2 * P - K) / S + 1), f32>
+    {
+        Tensor::new()
+    }
+
+    pub fn input_from_data(
+        &self,
+        data: [f32; IC * IL],
+    ) -> Tensor<{ IC * IL }, 2, shape_ty!(IC, IL), f32> {
+        Tensor::from(data)
+            .try_reshape()
+            .expect("IC * IL elements always fill (IC, IL)")
+    }
+
+    pub fn forward(
+        &self,
+        input: &Tensor<{ IC * IL }, 2, shape_ty!(IC, IL), f32>,
+        output: &mut Tensor<
+            { OC * ((IL + 2 * P - K) / S + 1) },
+            2,
+            shape_ty!(OC, (IL + 2 * P - K) / S + 1),
+            f32,
+        >,
+    ) {
+        let out_l = (IL + 2 * P - K) / S + 1;
+
+        for oc in 0..OC {
+            let filter = &self.data[oc].data;
+
+            for x in 0..out_l {
+                let mut sum = 0.0;
+
+                for k in 0..K {
+                    // zero padding outside the input
+                    let in_x = (x * S + k) as isize - P as isize;
+                    if in_x < 0 || in_x >= IL as isize {
+                        continue;
+                    }
+
+                    for ic in 0..IC {
+                        sum += filter[k * IC + ic] * input.data[ic * IL + in_x as usize];
+                    }
+                }
+
+                output.data[oc * out_l + x] = sum;
+            }
+        }
+    }
+}
+
+impl<
+    const IL: usize,
+    const IC: usize,
+    const K: usize,
+    const OC: usize,
+    const S: usize,
+    const P: usize,
+> ConvIO for Conv1D<IL, IC, K, OC, S, P>
+where
+    Tensor<{ IC * IL }, 2, shape_ty!(IC, IL), f32>: Sized,
+    Tensor<{ K * IC }, 2, shape_ty!(K, IC), f32>: Sized,
+    Tensor<{ OC * ((IL + 2 * P - K) / S + 1) }, 2, shape_ty!(OC, (IL + 2 * P - K) / S + 1), f32>:
+        Sized,
+{
+    const N: usize = IC * IL;
+    type Input = Tensor<{ IC * IL }, 2, shape_ty!(IC, IL), f32>;
+    type Output = Tensor<{ OC * ((IL + 2 * P - K) / S + 1) }, 2, Self::OutputShape, f32>;
+    type InputShape = shape_ty!(IC, IL);
+    type OutputShape = shape_ty!(OC, (IL + 2 * P - K) / S + 1);
+    type FilterShape = shape_ty!(K, IC);
+}
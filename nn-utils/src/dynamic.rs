@@ -0,0 +1,532 @@
+use crate::layerable::LayerKind;
+use std::{error::Error, fmt};
+
+/// Shape of the activations flowing between layers: `(channels, height, width)`
+///
+/// Flat vectors are `(n, 1, 1)`, which is what a bare `usize` converts into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DynShape {
+    pub channels: usize,
+    pub height: usize,
+    pub width: usize,
+}
+
+impl DynShape {
+    pub fn len(&self) -> usize {
+        self.channels * self.height * self.width
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl From<usize> for DynShape {
+    fn from(n: usize) -> Self {
+        Self {
+            channels: n,
+            height: 1,
+            width: 1,
+        }
+    }
+}
+
+impl From<(usize, usize, usize)> for DynShape {
+    fn from((channels, height, width): (usize, usize, usize)) -> Self {
+        Self {
+            channels,
+            height,
+            width,
+        }
+    }
+}
+
+/// A layer whose sizes are only known at runtime
+pub trait DynLayer: fmt::Debug {
+    fn forward(&self, input: &[f32]) -> Vec<f32>;
+    fn output_shape(&self) -> DynShape;
+
+    /// Every trainable parameter group, e.g. a dense layer's weights then its biases; empty for
+    /// layers without parameters
+    ///
+    /// Dense and convolutional layers start out random, in `[0, 1)`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::dynamic::build_from_spec;
+    /// use nn_utils::LayerKind;
+    ///
+    /// let mut net = build_from_spec(4, &[LayerKind::Dense { output: 3 }]).unwrap();
+    /// assert!(net.forward(&[1.0; 4]).iter().any(|&y| y != 0.0));
+    ///
+    /// let layer = &mut net.layers_mut()[0];
+    /// let groups = layer.params_mut();
+    /// assert_eq!(groups.iter().map(|g| g.len()).collect::<Vec<_>>(), [12, 3]);
+    ///
+    /// for group in groups {
+    ///     group.fill(0.5);
+    /// }
+    /// assert_eq!(net.forward(&[1.0; 4]), [2.5; 3]);
+    /// ```
+    fn params_mut(&mut self) -> Vec<&mut [f32]> {
+        Vec::new()
+    }
+}
+
+/// Error from [`build_from_spec`] naming the offending layer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecError {
+    pub layer: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "layer {}: {}", self.layer, self.reason)
+    }
+}
+
+impl Error for SpecError {}
+
+#[derive(Debug)]
+pub struct DynNetwork {
+    input: DynShape,
+    layers: Vec<Box<dyn DynLayer>>,
+}
+
+impl DynNetwork {
+    pub fn input_shape(&self) -> DynShape {
+        self.input
+    }
+
+    pub fn output_shape(&self) -> DynShape {
+        self.layers.last().map_or(self.input, |l| l.output_shape())
+    }
+
+    pub fn layers(&self) -> &[Box<dyn DynLayer>] {
+        &self.layers
+    }
+
+    pub fn layers_mut(&mut self) -> &mut [Box<dyn DynLayer>] {
+        &mut self.layers
+    }
+
+    pub fn forward(&self, input: &[f32]) -> Vec<f32> {
+        assert_eq!(input.len(), self.input.len(), "wrong input size");
+
+        self.layers
+            .iter()
+            .fold(input.to_vec(), |x, layer| layer.forward(&x))
+    }
+}
+
+/// Builds a network from layer specs, checking that every layer fits the shape before it
+///
+/// Unlike `network!`, the architecture need not be known at compile time (e.g. it can come from a
+/// config file), at the cost of validating shapes at runtime.
+///
+/// # Examples
+///
+/// ```rust
+/// use nn_utils::dynamic::build_from_spec;
+/// use nn_utils::LayerKind;
+///
+/// let net = build_from_spec(
+///     (1, 8, 8),
+///     &[
+///         LayerKind::Conv { out_channels: 4, kernel: 3, stride: 1, padding: 0 },
+///         LayerKind::ReLU { width: 4 * 6 * 6 },
+///         LayerKind::Dense { output: 10 },
+///         LayerKind::Softmax { width: 10 },
+///     ],
+/// )
+/// .unwrap();
+///
+/// let out = net.forward(&[0.5; 64]);
+/// assert_eq!(out.len(), 10);
+/// assert!((out.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+///
+/// // pooling halves each side
+/// let pooled = build_from_spec(
+///     (2, 8, 8),
+///     &[LayerKind::MaxPool { kernel: 2, stride: 2 }, LayerKind::AvgPool { kernel: 2, stride: 2 }],
+/// )
+/// .unwrap();
+/// assert_eq!(pooled.output_shape(), (2, 2, 2).into());
+/// assert_eq!(pooled.forward(&[1.5; 128]), [1.5; 8]);
+///
+/// // a 9x9 kernel does not fit an 8x8 input
+/// let conv = LayerKind::Conv { out_channels: 1, kernel: 9, stride: 1, padding: 0 };
+/// assert!(build_from_spec((1, 8, 8), &[conv]).is_err());
+/// ```
+pub fn build_from_spec(
+    input: impl Into<DynShape>,
+    kinds: &[LayerKind],
+) -> Result<DynNetwork, SpecError> {
+    let input = input.into();
+    let mut shape = input;
+    let mut layers: Vec<Box<dyn DynLayer>> = Vec::with_capacity(kinds.len());
+
+    for (i, kind) in kinds.iter().enumerate() {
+        let err = |reason: String| SpecError { layer: i, reason };
+
+        // activations declare their width, which has to agree with the running shape
+        let check_width = |width: usize| {
+            if width == shape.len() {
+                Ok(())
+            } else {
+                Err(err(format!(
+                    "width {width} does not match incoming size {}",
+                    shape.len()
+                )))
+            }
+        };
+
+        let layer: Box<dyn DynLayer> = match *kind {
+            LayerKind::Dense { output } => Box::new(Dense::new(shape.len(), output)),
+            LayerKind::ReLU { width } => {
+                check_width(width)?;
+                Box::new(Elementwise::new(shape, |x| x.max(0.0)))
+            }
+            LayerKind::Sigmoid { width } => {
+                check_width(width)?;
+                Box::new(Elementwise::new(shape, |x| 1.0 / (1.0 + (-x).exp())))
+            }
+            LayerKind::Tanh { width } => {
+                check_width(width)?;
+                Box::new(Elementwise::new(shape, f32::tanh))
+            }
+            LayerKind::LeakyReLU { width, slope } => {
+                check_width(width)?;
+                Box::new(Elementwise::new(
+                    shape,
+                    move |x| if x > 0.0 { x } else { slope * x },
+                ))
+            }
+            LayerKind::ELU { width, alpha } => {
+                check_width(width)?;
+                Box::new(Elementwise::new(shape, move |x| {
+                    if x > 0.0 { x } else { alpha * x.exp_m1() }
+                }))
+            }
+            LayerKind::SiLU { width, beta } => {
+                check_width(width)?;
+                Box::new(Elementwise::new(shape, move |x| {
+                    x / (1.0 + (-beta * x).exp())
+                }))
+            }
+            LayerKind::GELU { width } => {
+                check_width(width)?;
+                Box::new(Elementwise::new(shape, |x| {
+                    0.5 * x * (1.0 + (0.797_884_6 * (x + 0.044_715 * x.powi(3))).tanh())
+                }))
+            }
+            LayerKind::Softmax { width } => {
+                check_width(width)?;
+                Box::new(Softmax { shape })
+            }
+            LayerKind::LayerNorm { width, eps } => {
+                check_width(width)?;
+                Box::new(LayerNorm { shape, eps })
+            }
+            LayerKind::Conv {
+                out_channels,
+                kernel,
+                stride,
+                padding,
+            } => {
+                if stride == 0 {
+                    return Err(err("stride must be at least 1".into()));
+                }
+                if kernel > shape.height + 2 * padding || kernel > shape.width + 2 * padding {
+                    return Err(err(format!(
+                        "kernel {kernel} does not fit a {}x{} input with padding {padding}",
+                        shape.height, shape.width
+                    )));
+                }
+                Box::new(Conv::new(shape, out_channels, kernel, stride, padding))
+            }
+            LayerKind::MaxPool { kernel, stride } | LayerKind::AvgPool { kernel, stride } => {
+                if kernel == 0 || stride == 0 {
+                    return Err(err("window and stride must be at least 1".into()));
+                }
+                if kernel > shape.height || kernel > shape.width {
+                    return Err(err(format!(
+                        "window {kernel} does not fit a {}x{} input",
+                        shape.height, shape.width
+                    )));
+                }
+                let max = matches!(kind, LayerKind::MaxPool { .. });
+                Box::new(Pool::new(shape, kernel, stride, max))
+            }
+        };
+
+        shape = layer.output_shape();
+        layers.push(layer);
+    }
+
+    Ok(DynNetwork { input, layers })
+}
+
+#[derive(Debug)]
+struct Dense {
+    input: usize,
+    output: usize,
+    // row-major, one row of `input` weights per output
+    weights: Vec<f32>,
+    biases: Vec<f32>,
+}
+
+impl Dense {
+    // random like `Conv`, so a freshly built network doesn't output zeros
+    fn new(input: usize, output: usize) -> Self {
+        let mut weights = vec![0.0; input * output];
+        let mut biases = vec![0.0; output];
+        rand::fill(&mut weights[..]);
+        rand::fill(&mut biases[..]);
+
+        Self {
+            input,
+            output,
+            weights,
+            biases,
+        }
+    }
+}
+
+impl DynLayer for Dense {
+    fn forward(&self, input: &[f32]) -> Vec<f32> {
+        self.weights
+            .chunks_exact(self.input)
+            .zip(&self.biases)
+            .map(|(row, b)| b + row.iter().zip(input).map(|(w, x)| w * x).sum::<f32>())
+            .collect()
+    }
+
+    fn output_shape(&self) -> DynShape {
+        self.output.into()
+    }
+
+    fn params_mut(&mut self) -> Vec<&mut [f32]> {
+        vec![&mut self.weights, &mut self.biases]
+    }
+}
+
+struct Elementwise<F> {
+    shape: DynShape,
+    f: F,
+}
+
+impl<F: Fn(f32) -> f32> Elementwise<F> {
+    fn new(shape: DynShape, f: F) -> Self {
+        Self { shape, f }
+    }
+}
+
+impl<F> fmt::Debug for Elementwise<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Elementwise")
+            .field("shape", &self.shape)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F: Fn(f32) -> f32> DynLayer for Elementwise<F> {
+    fn forward(&self, input: &[f32]) -> Vec<f32> {
+        input.iter().map(|&x| (self.f)(x)).collect()
+    }
+
+    fn output_shape(&self) -> DynShape {
+        self.shape
+    }
+}
+
+#[derive(Debug)]
+struct Softmax {
+    shape: DynShape,
+}
+
+impl DynLayer for Softmax {
+    fn forward(&self, input: &[f32]) -> Vec<f32> {
+        let max = input.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let exps: Vec<f32> = input.iter().map(|x| (x - max).exp()).collect();
+        let sum: f32 = exps.iter().sum();
+        exps.into_iter().map(|e| e / sum).collect()
+    }
+
+    fn output_shape(&self) -> DynShape {
+        self.shape
+    }
+}
+
+// `gamma = 1` and `beta = 0`, like a fresh `network::LayerNorm`
+#[derive(Debug)]
+struct LayerNorm {
+    shape: DynShape,
+    eps: f32,
+}
+
+impl DynLayer for LayerNorm {
+    fn forward(&self, input: &[f32]) -> Vec<f32> {
+        let n = input.len() as f32;
+        let mean = input.iter().sum::<f32>() / n;
+        let var = input.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / n;
+        let inv_std = 1.0 / (var + self.eps).sqrt();
+        input.iter().map(|x| (x - mean) * inv_std).collect()
+    }
+
+    fn output_shape(&self) -> DynShape {
+        self.shape
+    }
+}
+
+// Same layouts as `conv::Conv`: input is (IC, IH, IW), each filter is (K, K, IC)
+#[derive(Debug)]
+struct Conv {
+    input: DynShape,
+    output: DynShape,
+    kernel: usize,
+    stride: usize,
+    padding: usize,
+    filters: Vec<f32>,
+}
+
+impl Conv {
+    fn new(
+        input: DynShape,
+        out_channels: usize,
+        kernel: usize,
+        stride: usize,
+        padding: usize,
+    ) -> Self {
+        let output = DynShape {
+            channels: out_channels,
+            height: (input.height + 2 * padding - kernel) / stride + 1,
+            width: (input.width + 2 * padding - kernel) / stride + 1,
+        };
+
+        let mut filters = vec![0.0; out_channels * kernel * kernel * input.channels];
+        rand::fill(&mut filters[..]);
+
+        Self {
+            input,
+            output,
+            kernel,
+            stride,
+            padding,
+            filters,
+        }
+    }
+}
+
+impl DynLayer for Conv {
+    fn forward(&self, input: &[f32]) -> Vec<f32> {
+        let DynShape {
+            channels: ic_n,
+            height: ih,
+            width: iw,
+        } = self.input;
+        let k = self.kernel;
+        let mut out = vec![0.0; self.output.len()];
+
+        for oc in 0..self.output.channels {
+            let filter = &self.filters[oc * k * k * ic_n..][..k * k * ic_n];
+
+            for y in 0..self.output.height {
+                for x in 0..self.output.width {
+                    let mut sum = 0.0;
+
+                    for ky in 0..k {
+                        for kx in 0..k {
+                            let in_y = (y * self.stride + ky) as isize - self.padding as isize;
+                            let in_x = (x * self.stride + kx) as isize - self.padding as isize;
+
+                            // zero padding outside the input
+                            if in_y < 0 || in_y >= ih as isize || in_x < 0 || in_x >= iw as isize {
+                                continue;
+                            }
+
+                            for ic in 0..ic_n {
+                                let input_val =
+                                    input[(ic * ih + in_y as usize) * iw + in_x as usize];
+                                sum += filter[(ky * k + kx) * ic_n + ic] * input_val;
+                            }
+                        }
+                    }
+
+                    out[(oc * self.output.height + y) * self.output.width + x] = sum;
+                }
+            }
+        }
+
+        out
+    }
+
+    fn output_shape(&self) -> DynShape {
+        self.output
+    }
+
+    fn params_mut(&mut self) -> Vec<&mut [f32]> {
+        vec![&mut self.filters]
+    }
+}
+
+// `MaxPool` or `AvgPool`, per channel, without padding
+#[derive(Debug)]
+struct Pool {
+    input: DynShape,
+    output: DynShape,
+    kernel: usize,
+    stride: usize,
+    max: bool,
+}
+
+impl Pool {
+    fn new(input: DynShape, kernel: usize, stride: usize, max: bool) -> Self {
+        let output = DynShape {
+            channels: input.channels,
+            height: (input.height - kernel) / stride + 1,
+            width: (input.width - kernel) / stride + 1,
+        };
+
+        Self {
+            input,
+            output,
+            kernel,
+            stride,
+            max,
+        }
+    }
+}
+
+impl DynLayer for Pool {
+    fn forward(&self, input: &[f32]) -> Vec<f32> {
+        let (ih, iw, k) = (self.input.height, self.input.width, self.kernel);
+        let mut out = Vec::with_capacity(self.output.len());
+
+        for c in 0..self.output.channels {
+            for y in 0..self.output.height {
+                for x in 0..self.output.width {
+                    let window = (0..k).flat_map(|ky| {
+                        (0..k).map(move |kx| {
+                            input[(c * ih + y * self.stride + ky) * iw + x * self.stride + kx]
+                        })
+                    });
+
+                    out.push(if self.max {
+                        window.fold(f32::NEG_INFINITY, f32::max)
+                    } else {
+                        window.sum::<f32>() / (k * k) as f32
+                    });
+                }
+            }
+        }
+
+        out
+    }
+
+    fn output_shape(&self) -> DynShape {
+        self.output
+    }
+}
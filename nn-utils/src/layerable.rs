@@ -3,12 +3,21 @@ pub enum LayerKind {
     Dense { output: usize },
     ReLU { width: usize },
     Sigmoid { width: usize },
+    Tanh { width: usize },
+    LeakyReLU { width: usize, slope: f32 },
+    ELU { width: usize, alpha: f32 },
+    SiLU { width: usize, beta: f32 },
+    GELU { width: usize },
+    Softmax { width: usize },
+    LayerNorm { width: usize, eps: f32 },
     Conv {
         out_channels: usize,
         kernel: usize,
         stride: usize,
         padding: usize,
     },
+    MaxPool { kernel: usize, stride: usize },
+    AvgPool { kernel: usize, stride: usize },
 }
 
 pub trait Layerable {
@@ -1,16 +1,15 @@
-#![allow(internal_features)]
 #![allow(incomplete_features)]
 #![feature(generic_const_exprs)]
-#![feature(core_intrinsics)]
-#![feature(generic_const_items)]
-#![feature(specialization)]
 
 #[macro_use]
 mod tensor;
 
 pub mod conv;
 
-pub use tensor::Tensor;
+pub use tensor::{
+    ArraySize, ConcatError, DynTensor, ReshapeError, Scalar, ShapeDims, SliceError, Tensor,
+    TensorView,
+};
 pub use layerable::{LayerKind, Layerable};
 
 // helper stuff for proc macro
@@ -20,3 +19,17 @@ pub mod network;
 pub mod autodiff;
 
 pub mod layerable;
+
+// runtime-built networks from `LayerKind`s
+pub mod dynamic;
+
+// runtime-built networks from boxed layers
+pub mod sequential;
+
+pub mod optim;
+
+// kernels shared by the layers
+pub mod linalg;
+
+// fitting a network sample by sample
+pub mod train;
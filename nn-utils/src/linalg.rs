@@ -0,0 +1,39 @@
+/// Matrix-vector product plus bias: `out = weights * input + biases`
+///
+/// `weights` is row-major, one row of `IN` per output. Each output is its bias plus the row's
+/// products, summed left to right; floating point addition isn't associative, so the order is kept
+/// fixed rather than letting the compiler split the sum into vector lanes.
+///
+/// # Examples
+///
+/// ```rust
+/// use nn_utils::linalg::gemv;
+///
+/// let weights: [[f32; 3]; 4] =
+///     std::array::from_fn(|o| std::array::from_fn(|i| (o * 3 + i) as f32 - 5.0));
+/// let biases = [0.5, -1.0, 0.0, 2.0];
+/// let input = [0.25, -2.0, 3.0];
+///
+/// let mut out = [0.0; 4];
+/// gemv(&weights, &biases, &input, &mut out);
+///
+/// for o in 0..4 {
+///     let mut expected = biases[o];
+///     for i in 0..3 {
+///         expected += weights[o][i] * input[i];
+///     }
+///     assert_eq!(out[o], expected);
+/// }
+/// assert_eq!(out, [-1.75, 0.5, 5.25, 11.0]);
+/// ```
+#[inline]
+pub fn gemv<const IN: usize, const OUT: usize>(
+    weights: &[[f32; IN]; OUT],
+    biases: &[f32; OUT],
+    input: &[f32; IN],
+    out: &mut [f32; OUT],
+) {
+    for ((out, row), &bias) in out.iter_mut().zip(weights).zip(biases) {
+        *out = row.iter().zip(input).fold(bias, |sum, (w, x)| sum + w * x);
+    }
+}
@@ -1,8 +1,18 @@
+use crate::{linalg::gemv, tensor::Tensor, train::NetworkTrait};
+use std::{
+    error::Error,
+    fmt,
+    io::{self, Read, Write},
+};
+
 // Define the DenseLayer struct with weights and biases
 #[derive(Debug)]
 pub struct DenseLayer<const IN: usize, const OUT: usize> {
     weights: Box<[[f32; IN]; OUT]>,
     biases: Box<[f32; OUT]>,
+    // accumulated by `backward`, same layout as the parameters
+    weight_grads: Box<[[f32; IN]; OUT]>,
+    bias_grads: Box<[f32; OUT]>,
 }
 
 // Rectified Linear Unit
@@ -13,6 +23,95 @@ pub struct ReLU<const N: usize>;
 #[derive(Debug)]
 pub struct Sigmoid<const N: usize>;
 
+// Hyperbolic tangent
+#[derive(Debug)]
+pub struct Tanh<const N: usize>;
+
+// Leaky Rectified Linear Unit: `slope * x` instead of zero for negative inputs
+#[derive(Debug)]
+pub struct LeakyReLU<const N: usize> {
+    slope: f32,
+}
+
+// Exponential Linear Unit: `alpha * (e^x - 1)` for negative inputs
+#[derive(Debug)]
+pub struct ELU<const N: usize> {
+    alpha: f32,
+}
+
+// Gaussian Error Linear Unit: `x * Φ(x)`, with `Φ` the standard normal CDF, either exactly or
+// through its tanh approximation
+#[derive(Debug)]
+pub struct GELU<const N: usize> {
+    exact: bool,
+}
+
+// Softmax: normalizes the whole input vector into a probability distribution
+#[derive(Debug)]
+pub struct Softmax<const N: usize>;
+
+// Sigmoid Linear Unit (swish): `x * sigmoid(beta * x)`
+#[derive(Debug)]
+pub struct SiLU<const N: usize> {
+    beta: f32,
+}
+
+// Layer normalization: standardizes each sample by its own mean and variance, then scales by
+// `gamma` and shifts by `beta`
+#[derive(Debug)]
+pub struct LayerNorm<const N: usize> {
+    gamma: [f32; N],
+    beta: [f32; N],
+    eps: f32,
+}
+
+// Batch normalization: standardizes each feature over a batch, then scales by `gamma` and shifts by
+// `beta`
+#[derive(Debug)]
+pub struct BatchNorm<const N: usize> {
+    gamma: [f32; N],
+    beta: [f32; N],
+    // estimates of the data statistics, used outside of training
+    running_mean: [f32; N],
+    running_var: [f32; N],
+    momentum: f32,
+    eps: f32,
+    training: bool,
+}
+
+// Global average pooling: reduces each channel of a `(IC, IH, IW)` feature map to its mean
+#[derive(Debug)]
+pub struct GlobalAvgPool<const IW: usize, const IH: usize, const IC: usize>;
+
+// Max pooling: keeps the largest value of each `K x K` window of a `(IC, IH, IW)` feature map,
+// moving the window `S` positions at a time
+#[derive(Debug)]
+pub struct MaxPool<
+    const IW: usize,
+    const IH: usize,
+    const IC: usize,
+    const K: usize,
+    const S: usize,
+>;
+
+// Average pooling: like `MaxPool`, but keeps the mean of each window
+#[derive(Debug)]
+pub struct AvgPool<
+    const IW: usize,
+    const IH: usize,
+    const IC: usize,
+    const K: usize,
+    const S: usize,
+>;
+
+// Embedding: a lookup table from integer indices (e.g. token ids) to learned vectors
+#[derive(Debug)]
+pub struct Embedding<const VOCAB: usize, const DIM: usize> {
+    table: Box<[[f32; DIM]; VOCAB]>,
+    // accumulated by `backward`, same layout as `table`
+    grads: Box<[[f32; DIM]; VOCAB]>,
+}
+
 // Forward pass implementation for ReLU
 impl<const N: usize> ReLU<N> {
     pub fn init() -> Self {
@@ -23,8 +122,29 @@ impl<const N: usize> ReLU<N> {
     // where
     //     I: AsRef<[f32; N]>,
     {
-        for i in 0..N {
-            output[i] = input.as_ref()[i].max(0.0);
+        for (o, i) in output[..N].iter_mut().zip(&input[..N]) {
+            *o = i.max(0.0);
+        }
+    }
+
+    /// The subgradient at zero is taken to be zero
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::network::ReLU;
+    ///
+    /// let mut grad = [0.0; 3];
+    /// ReLU::<3>::init().backward(&[-1.0, 0.0, 2.0], &[3.0; 3], &mut grad);
+    /// assert_eq!(grad, [0.0, 0.0, 3.0]);
+    /// ```
+    pub fn backward(&self, input: &[f32], grad_output: &[f32], grad_input: &mut [f32]) {
+        for ((g, &i), go) in grad_input[..N]
+            .iter_mut()
+            .zip(&input[..N])
+            .zip(&grad_output[..N])
+        {
+            *g = if i > 0.0 { *go } else { 0.0 };
         }
     }
 }
@@ -40,8 +160,586 @@ impl<const N: usize> Sigmoid<N> {
     // where
     //     I: AsRef<[f32; N]>,
     {
+        for (o, i) in output[..N].iter_mut().zip(&input[..N]) {
+            *o = sigmoid(*i);
+        }
+    }
+
+    /// Multiplies the upstream gradient by `s * (1 - s)`, where `s = sigmoid(x)`
+    ///
+    /// # Examples
+    ///
+    /// Agrees with a central difference of `forward`:
+    /// ```rust
+    /// use nn_utils::network::Sigmoid;
+    ///
+    /// let sigmoid = Sigmoid::<1>::init();
+    /// let (x, h) = (0.7f32, 1e-3);
+    ///
+    /// let (mut lo, mut hi, mut grad) = ([0.0], [0.0], [0.0]);
+    /// sigmoid.forward(&[x - h], &mut lo);
+    /// sigmoid.forward(&[x + h], &mut hi);
+    /// sigmoid.backward(&[x], &[1.0], &mut grad);
+    ///
+    /// assert!(((hi[0] - lo[0]) / (2.0 * h) - grad[0]).abs() < 1e-3);
+    /// ```
+    pub fn backward(&self, input: &[f32], grad_output: &[f32], grad_input: &mut [f32]) {
+        for ((g, &i), go) in grad_input[..N]
+            .iter_mut()
+            .zip(&input[..N])
+            .zip(&grad_output[..N])
+        {
+            let s = sigmoid(i);
+            *g = go * s * (1.0 - s);
+        }
+    }
+}
+
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+impl<const N: usize> Tanh<N> {
+    pub fn init() -> Self {
+        Tanh
+    }
+
+    pub fn forward(&self, input: &[f32], output: &mut [f32]) {
+        for (o, i) in output[..N].iter_mut().zip(&input[..N]) {
+            *o = i.tanh();
+        }
+    }
+
+    /// Multiplies the upstream gradient by `1 - tanh(x)^2`, where `x` is the input seen by `forward`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::network::Tanh;
+    ///
+    /// let mut grad_input = [0.0];
+    /// Tanh::<1>::init().backward(&[0.0], &[1.0], &mut grad_input);
+    /// assert_eq!(grad_input, [1.0]);
+    /// ```
+    pub fn backward(&self, input: &[f32], grad_output: &[f32], grad_input: &mut [f32]) {
+        for ((g, i), go) in grad_input[..N]
+            .iter_mut()
+            .zip(&input[..N])
+            .zip(&grad_output[..N])
+        {
+            *g = go * (1.0 - i.tanh().powi(2));
+        }
+    }
+}
+
+impl<const N: usize> LeakyReLU<N> {
+    /// Uses the conventional slope of `0.01`
+    pub fn init() -> Self {
+        Self::new(0.01)
+    }
+
+    pub fn new(slope: f32) -> Self {
+        LeakyReLU { slope }
+    }
+
+    pub fn forward(&self, input: &[f32], output: &mut [f32]) {
+        for (o, &i) in output[..N].iter_mut().zip(&input[..N]) {
+            *o = if i > 0.0 { i } else { self.slope * i };
+        }
+    }
+
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::network::LeakyReLU;
+    ///
+    /// let leaky = LeakyReLU::<2>::new(0.1);
+    ///
+    /// let mut out = [0.0; 2];
+    /// leaky.forward(&[-2.0, 3.0], &mut out);
+    /// assert_eq!(out, [-0.2, 3.0]);
+    ///
+    /// leaky.backward(&[-2.0, 3.0], &[1.0, 1.0], &mut out);
+    /// assert_eq!(out, [0.1, 1.0]);
+    /// ```
+    pub fn backward(&self, input: &[f32], grad_output: &[f32], grad_input: &mut [f32]) {
+        for ((g, &i), go) in grad_input[..N]
+            .iter_mut()
+            .zip(&input[..N])
+            .zip(&grad_output[..N])
+        {
+            *g = if i > 0.0 { *go } else { self.slope * go };
+        }
+    }
+}
+
+impl<const N: usize> ELU<N> {
+    /// Uses the conventional `alpha` of `1.0`
+    pub fn init() -> Self {
+        Self::new(1.0)
+    }
+
+    pub fn new(alpha: f32) -> Self {
+        ELU { alpha }
+    }
+
+    pub fn forward(&self, input: &[f32], output: &mut [f32]) {
+        for (o, &i) in output[..N].iter_mut().zip(&input[..N]) {
+            *o = if i > 0.0 { i } else { self.alpha * i.exp_m1() };
+        }
+    }
+
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::network::ELU;
+    ///
+    /// let elu = ELU::<1>::new(2.0);
+    ///
+    /// let mut out = [0.0];
+    /// elu.forward(&[-1.0], &mut out);
+    /// assert!((out[0] - 2.0 * ((-1.0f32).exp() - 1.0)).abs() < 1e-6);
+    ///
+    /// elu.backward(&[-1.0], &[1.0], &mut out);
+    /// assert!((out[0] - 2.0 * (-1.0f32).exp()).abs() < 1e-6);
+    /// ```
+    pub fn backward(&self, input: &[f32], grad_output: &[f32], grad_input: &mut [f32]) {
+        for ((g, &i), go) in grad_input[..N]
+            .iter_mut()
+            .zip(&input[..N])
+            .zip(&grad_output[..N])
+        {
+            // d/dx alpha * (e^x - 1) = alpha * e^x
+            *g = if i > 0.0 {
+                *go
+            } else {
+                self.alpha * i.exp() * go
+            };
+        }
+    }
+}
+
+impl<const N: usize> SiLU<N> {
+    /// Standard swish, `beta = 1`
+    pub fn init() -> Self {
+        Self::default()
+    }
+
+    pub fn new(beta: f32) -> Self {
+        SiLU { beta }
+    }
+
+    pub fn forward(&self, input: &[f32], output: &mut [f32]) {
+        for (o, &i) in output[..N].iter_mut().zip(&input[..N]) {
+            *o = i * sigmoid(self.beta * i);
+        }
+    }
+
+    /// # Examples
+    ///
+    /// Agrees with a central difference of `forward`:
+    /// ```rust
+    /// use nn_utils::network::SiLU;
+    ///
+    /// let silu = SiLU::<2>::new(1.5);
+    /// let (x, h) = ([-0.8f32, 1.2], 1e-3);
+    ///
+    /// let (mut lo, mut hi, mut grad) = ([0.0; 2], [0.0; 2], [0.0; 2]);
+    /// silu.forward(&x.map(|x| x - h), &mut lo);
+    /// silu.forward(&x.map(|x| x + h), &mut hi);
+    /// silu.backward(&x, &[1.0; 2], &mut grad);
+    ///
+    /// for i in 0..2 {
+    ///     assert!(((hi[i] - lo[i]) / (2.0 * h) - grad[i]).abs() < 1e-3);
+    /// }
+    /// ```
+    pub fn backward(&self, input: &[f32], grad_output: &[f32], grad_input: &mut [f32]) {
+        for ((g, &i), go) in grad_input[..N]
+            .iter_mut()
+            .zip(&input[..N])
+            .zip(&grad_output[..N])
+        {
+            let s = sigmoid(self.beta * i);
+            *g = go * (s + self.beta * i * s * (1.0 - s));
+        }
+    }
+}
+
+// sqrt(2 / pi) and the cubic coefficient of the tanh approximation
+const GELU_K: f32 = 0.797_884_6;
+const GELU_C: f32 = 0.044_715;
+
+impl<const N: usize> GELU<N> {
+    /// The tanh approximation, `0.5 * x * (1 + tanh(sqrt(2 / pi) * (x + 0.044715 * x^3)))`
+    pub fn init() -> Self {
+        GELU { exact: false }
+    }
+
+    /// `x * Φ(x)` computed through the error function
+    pub fn exact() -> Self {
+        GELU { exact: true }
+    }
+
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::network::GELU;
+    ///
+    /// let mut out = [0.0; 3];
+    /// GELU::<3>::init().forward(&[0.0, 10.0, -10.0], &mut out);
+    /// assert_eq!(out[0], 0.0);
+    /// // close to the identity for large inputs, and to zero for very negative ones
+    /// assert!((out[1] - 10.0).abs() < 1e-5 && out[2].abs() < 1e-5);
+    ///
+    /// // the approximation stays within about 1e-3 of the exact function
+    /// let x = [-2.0, -0.5, 0.7, 1.5];
+    /// let (mut approx, mut exact) = ([0.0; 4], [0.0; 4]);
+    /// GELU::<4>::init().forward(&x, &mut approx);
+    /// GELU::<4>::exact().forward(&x, &mut exact);
+    /// for (a, e) in approx.iter().zip(&exact) {
+    ///     assert!((a - e).abs() < 1e-3);
+    /// }
+    /// ```
+    pub fn forward(&self, input: &[f32], output: &mut [f32]) {
+        for (o, &i) in output[..N].iter_mut().zip(&input[..N]) {
+            *o = if self.exact {
+                i * normal_cdf(i)
+            } else {
+                0.5 * i * (1.0 + (GELU_K * (i + GELU_C * i.powi(3))).tanh())
+            };
+        }
+    }
+
+    /// # Examples
+    ///
+    /// Both forms agree with a central difference of `forward`:
+    /// ```rust
+    /// use nn_utils::network::GELU;
+    ///
+    /// for gelu in [GELU::<3>::init(), GELU::exact()] {
+    ///     let (x, h) = ([-1.3f32, 0.2, 2.1], 1e-3);
+    ///
+    ///     let (mut lo, mut hi, mut grad) = ([0.0; 3], [0.0; 3], [0.0; 3]);
+    ///     gelu.forward(&x.map(|x| x - h), &mut lo);
+    ///     gelu.forward(&x.map(|x| x + h), &mut hi);
+    ///     gelu.backward(&x, &[1.0; 3], &mut grad);
+    ///
+    ///     for i in 0..3 {
+    ///         assert!(((hi[i] - lo[i]) / (2.0 * h) - grad[i]).abs() < 1e-3);
+    ///     }
+    /// }
+    /// ```
+    pub fn backward(&self, input: &[f32], grad_output: &[f32], grad_input: &mut [f32]) {
+        for ((g, &i), go) in grad_input[..N]
+            .iter_mut()
+            .zip(&input[..N])
+            .zip(&grad_output[..N])
+        {
+            let d = if self.exact {
+                // Φ(x) + x * φ(x)
+                let pdf = (-0.5 * i * i).exp() / (2.0 * std::f32::consts::PI).sqrt();
+                normal_cdf(i) + i * pdf
+            } else {
+                let t = (GELU_K * (i + GELU_C * i.powi(3))).tanh();
+                0.5 * (1.0 + t) + 0.5 * i * (1.0 - t * t) * GELU_K * (1.0 + 3.0 * GELU_C * i * i)
+            };
+            *g = go * d;
+        }
+    }
+}
+
+fn normal_cdf(x: f32) -> f32 {
+    0.5 * (1.0 + erf(x / std::f32::consts::SQRT_2))
+}
+
+// Abramowitz & Stegun 7.1.26, accurate to about 1.5e-7
+fn erf(x: f32) -> f32 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let poly = t
+        * (0.254_829_6
+            + t * (-0.284_496_74 + t * (1.421_413_7 + t * (-1.453_152_1 + t * 1.061_405_4))));
+    (1.0 - poly * (-x * x).exp()).copysign(x)
+}
+
+impl<const N: usize> Default for SiLU<N> {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+// Unlike the element-wise activations, every output depends on every input
+impl<const N: usize> Softmax<N> {
+    pub fn init() -> Self {
+        Softmax
+    }
+
+    /// Numerically stable softmax (the maximum input is subtracted before exponentiating)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::network::Softmax;
+    ///
+    /// let mut out = [0.0; 4];
+    /// Softmax::<4>::init().forward(&[1.0, 2.0, 3.0, 1000.0], &mut out);
+    ///
+    /// assert!((out.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+    /// assert!(out.iter().all(|p| p.is_finite()));
+    /// ```
+    pub fn forward(&self, input: &[f32], output: &mut [f32]) {
+        let max = input[..N].iter().copied().fold(f32::NEG_INFINITY, f32::max);
+
+        let mut sum = 0.0;
+        for (o, i) in output[..N].iter_mut().zip(&input[..N]) {
+            *o = (i - max).exp();
+            sum += *o;
+        }
+
+        for o in &mut output[..N] {
+            *o /= sum;
+        }
+    }
+
+    /// Jacobian-vector product with the upstream gradient: `s_i * (g_i - sum_j s_j * g_j)`
+    pub fn backward(&self, input: &[f32], grad_output: &[f32], grad_input: &mut [f32]) {
+        // grad_input doubles as storage for the softmax itself
+        self.forward(input, grad_input);
+
+        let dot: f32 = grad_input[..N]
+            .iter()
+            .zip(&grad_output[..N])
+            .map(|(s, g)| s * g)
+            .sum();
+
+        for (g, go) in grad_input[..N].iter_mut().zip(&grad_output[..N]) {
+            *g *= go - dot;
+        }
+    }
+}
+
+impl<const N: usize> LayerNorm<N> {
+    /// Uses the conventional `eps` of `1e-5`
+    pub fn init() -> Self {
+        Self::new(1e-5)
+    }
+
+    /// `gamma = 1` and `beta = 0`, with `eps` added to the variance to keep constant inputs finite
+    pub fn new(eps: f32) -> Self {
+        LayerNorm {
+            gamma: [1.0; N],
+            beta: [0.0; N],
+            eps,
+        }
+    }
+
+    pub fn gamma_mut(&mut self) -> &mut [f32; N] {
+        &mut self.gamma
+    }
+
+    pub fn beta_mut(&mut self) -> &mut [f32; N] {
+        &mut self.beta
+    }
+
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::network::LayerNorm;
+    ///
+    /// let mut norm = LayerNorm::<3>::init();
+    /// *norm.beta_mut() = [0.5, -1.0, 2.0];
+    ///
+    /// // zero variance: every element sits at the mean, leaving just `beta`
+    /// let mut out = [0.0; 3];
+    /// norm.forward(&[4.0; 3], &mut out);
+    /// assert_eq!(out, [0.5, -1.0, 2.0]);
+    ///
+    /// *norm.beta_mut() = [0.0; 3];
+    /// norm.forward(&[1.0, 2.0, 3.0], &mut out);
+    /// let mean = out.iter().sum::<f32>() / 3.0;
+    /// let var = out.iter().map(|o| o * o).sum::<f32>() / 3.0;
+    /// assert!(mean.abs() < 1e-6 && (var - 1.0).abs() < 1e-4);
+    /// ```
+    pub fn forward(&self, input: &[f32], output: &mut [f32]) {
+        let n = N as f32;
+        let mean = input[..N].iter().sum::<f32>() / n;
+        let var = input[..N].iter().map(|x| (x - mean).powi(2)).sum::<f32>() / n;
+        let inv_std = 1.0 / (var + self.eps).sqrt();
+
         for i in 0..N {
-            output[i] = 1.0 / (1.0 + (-input.as_ref()[i]).exp());
+            output[i] = self.gamma[i] * (input[i] - mean) * inv_std + self.beta[i];
+        }
+    }
+
+    /// Backpropagates through the normalization to the input; `gamma` and `beta` are fixed, so
+    /// they get no gradients
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::network::LayerNorm;
+    ///
+    /// let mut norm = LayerNorm::<3>::init();
+    /// *norm.gamma_mut() = [1.5, 0.5, -1.0];
+    ///
+    /// let (x, go, h) = ([1.0, 2.0, 4.0], [0.3, -0.2, 0.7], 1e-2);
+    /// let loss = |x: &[f32; 3]| {
+    ///     let mut y = [0.0; 3];
+    ///     norm.forward(x, &mut y);
+    ///     y.iter().zip(&go).map(|(y, g)| y * g).sum::<f32>()
+    /// };
+    ///
+    /// let mut grad = [0.0; 3];
+    /// norm.backward(&x, &go, &mut grad);
+    /// for i in 0..3 {
+    ///     let (mut lo, mut hi) = (x, x);
+    ///     lo[i] -= h;
+    ///     hi[i] += h;
+    ///     let numeric = (loss(&hi) - loss(&lo)) / (2.0 * h);
+    ///     assert!((numeric - grad[i]).abs() < 1e-3);
+    /// }
+    /// ```
+    pub fn backward(&self, input: &[f32], grad_output: &[f32], grad_input: &mut [f32]) {
+        let n = N as f32;
+        let mean = input[..N].iter().sum::<f32>() / n;
+        let var = input[..N].iter().map(|x| (x - mean).powi(2)).sum::<f32>() / n;
+        let inv_std = 1.0 / (var + self.eps).sqrt();
+
+        // with `x_hat` the normalized input and `d = gamma * g`:
+        // dx = inv_std * (d - mean(d) - x_hat * mean(d * x_hat))
+        let (mut d_mean, mut d_x_hat_mean) = (0.0, 0.0);
+        for i in 0..N {
+            let d = self.gamma[i] * grad_output[i];
+            d_mean += d / n;
+            d_x_hat_mean += d * (input[i] - mean) * inv_std / n;
+        }
+
+        for i in 0..N {
+            let x_hat = (input[i] - mean) * inv_std;
+            let d = self.gamma[i] * grad_output[i];
+            grad_input[i] = inv_std * (d - d_mean - x_hat * d_x_hat_mean);
+        }
+    }
+}
+
+impl<const N: usize> BatchNorm<N> {
+    /// Starts in training mode with `gamma = 1`, `beta = 0`, a momentum of `0.1`, and `eps = 1e-5`
+    pub fn init() -> Self {
+        BatchNorm {
+            gamma: [1.0; N],
+            beta: [0.0; N],
+            running_mean: [0.0; N],
+            running_var: [1.0; N],
+            momentum: 0.1,
+            eps: 1e-5,
+            training: true,
+        }
+    }
+
+    /// How far each training batch moves the running statistics towards its own
+    pub fn with_momentum(mut self, momentum: f32) -> Self {
+        self.momentum = momentum;
+        self
+    }
+
+    pub fn train(&mut self) {
+        self.training = true;
+    }
+
+    pub fn eval(&mut self) {
+        self.training = false;
+    }
+
+    pub fn is_training(&self) -> bool {
+        self.training
+    }
+
+    pub fn gamma_mut(&mut self) -> &mut [f32; N] {
+        &mut self.gamma
+    }
+
+    pub fn beta_mut(&mut self) -> &mut [f32; N] {
+        &mut self.beta
+    }
+
+    pub fn running_mean(&self) -> &[f32; N] {
+        &self.running_mean
+    }
+
+    pub fn running_var(&self) -> &[f32; N] {
+        &self.running_var
+    }
+
+    /// Normalizes a single sample with the running statistics, whatever the mode
+    pub fn forward(&self, input: &[f32], output: &mut [f32]) {
+        self.normalize(input, output, &self.running_mean, &self.running_var);
+    }
+
+    /// In training mode, normalizes with the batch's own statistics and folds them into the running
+    /// ones; in eval mode, behaves like [`BatchNorm::forward`] on every sample
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::network::BatchNorm;
+    ///
+    /// let mut bn = BatchNorm::<2>::init();
+    /// let batch = [[1.0, 10.0], [5.0, 20.0], [3.0, 30.0]];
+    /// let mut out = [[0.0; 2]; 3];
+    ///
+    /// for _ in 0..200 {
+    ///     bn.forward_batch(&batch, &mut out);
+    /// }
+    /// assert!((bn.running_mean()[0] - 3.0).abs() < 1e-3);
+    /// assert!((bn.running_mean()[1] - 20.0).abs() < 1e-3);
+    ///
+    /// // training outputs are standardized per feature
+    /// let mean: f32 = out.iter().map(|o| o[1]).sum::<f32>() / 3.0;
+    /// assert!(mean.abs() < 1e-5);
+    ///
+    /// bn.eval();
+    /// bn.forward_batch(&[[3.0, 20.0]], &mut out[..1]);
+    /// assert!(out[0].iter().all(|o| o.abs() < 1e-3));
+    /// ```
+    pub fn forward_batch(&mut self, batch: &[[f32; N]], out: &mut [[f32; N]]) {
+        assert_eq!(batch.len(), out.len(), "batch and output sizes differ");
+
+        if !self.training || batch.is_empty() {
+            for (x, o) in batch.iter().zip(out) {
+                self.forward(x, o);
+            }
+            return;
+        }
+
+        let n = batch.len() as f32;
+        let mut mean = [0.0; N];
+        let mut var = [0.0; N];
+        for x in batch {
+            for (m, x) in mean.iter_mut().zip(x) {
+                *m += x / n;
+            }
+        }
+        for x in batch {
+            for ((v, x), m) in var.iter_mut().zip(x).zip(&mean) {
+                *v += (x - m).powi(2) / n;
+            }
+        }
+
+        for (x, o) in batch.iter().zip(out) {
+            self.normalize(x, o, &mean, &var);
+        }
+
+        // the running variance is unbiased, as the batch is only a sample
+        let correction = if batch.len() > 1 { n / (n - 1.0) } else { 1.0 };
+        for i in 0..N {
+            self.running_mean[i] += self.momentum * (mean[i] - self.running_mean[i]);
+            self.running_var[i] += self.momentum * (var[i] * correction - self.running_var[i]);
+        }
+    }
+
+    fn normalize(&self, input: &[f32], output: &mut [f32], mean: &[f32; N], var: &[f32; N]) {
+        for i in 0..N {
+            let x_hat = (input[i] - mean[i]) / (var[i] + self.eps).sqrt();
+            output[i] = self.gamma[i] * x_hat + self.beta[i];
         }
     }
 }
@@ -49,22 +747,652 @@ impl<const N: usize> Sigmoid<N> {
 // Initialize DenseLayer (simplified; real init would use proper randomization)
 impl<const IN: usize, const OUT: usize> DenseLayer<IN, OUT> {
     pub fn init() -> Self {
+        Self::from_parts(Box::new([[0.0; IN]; OUT]), Box::new([0.0; OUT]))
+    }
+
+    /// Builds a layer from existing parameters, e.g. pretrained weights
+    pub fn from_parts(weights: Box<[[f32; IN]; OUT]>, biases: Box<[f32; OUT]>) -> Self {
         Self {
-            weights: Box::new([[0.0; IN]; OUT]),
-            biases: Box::new([0.0; OUT]),
+            weights,
+            biases,
+            weight_grads: Box::new([[0.0; IN]; OUT]),
+            bias_grads: Box::new([0.0; OUT]),
         }
     }
 
-    // Forward pass for DenseLayer (basic implementation)
-    //
-    // used to be forward<I: AsRef<[f32; IN]>>(... input: I, ...)
+    /// Weights in row-major order: one row of `IN` weights per output neuron
+    pub fn weights(&self) -> &[[f32; IN]; OUT] {
+        &self.weights
+    }
+
+    pub fn weights_mut(&mut self) -> &mut [[f32; IN]; OUT] {
+        &mut self.weights
+    }
+
+    pub fn biases(&self) -> &[f32; OUT] {
+        &self.biases
+    }
+
+    pub fn biases_mut(&mut self) -> &mut [f32; OUT] {
+        &mut self.biases
+    }
+
+    /// Number of weights and biases, `IN * OUT + OUT`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::network::DenseLayer;
+    ///
+    /// let layer = DenseLayer::<784, 128>::init();
+    /// assert_eq!(layer.num_params(), 784 * 128 + 128);
+    /// assert_eq!(layer.flops(), 2 * 784 * 128);
+    /// ```
+    pub fn num_params(&self) -> usize {
+        IN * OUT + OUT
+    }
+
+    /// Floating point operations in one [`DenseLayer::forward`], counting a multiply-add as two
+    pub fn flops(&self) -> usize {
+        2 * IN * OUT
+    }
+
+    /// Computes `W * input + b` with [`gemv`], so each output is its bias plus the row's products,
+    /// summed left to right
+    ///
+    /// See `examples/dense_forward.rs` for timings.
+    #[inline]
     pub fn forward(&self, input: &[f32], output: &mut [f32]) {
-        for o in 0..OUT {
-            let mut sum = self.biases[o];
-            for i in 0..IN {
-                sum += self.weights[o][i] * input.as_ref()[i];
+        let input = input[..IN].try_into().unwrap();
+        let output = (&mut output[..OUT]).try_into().unwrap();
+        gemv(&self.weights, &self.biases, input, output);
+    }
+
+    /// Backpropagates `grad_output` (the loss gradient w.r.t. this layer's output) through the layer
+    ///
+    /// Writes `W^T * grad_output` into `grad_input` and accumulates the weight and bias gradients,
+    /// which are kept until [`DenseLayer::zero_grads`].
+    ///
+    /// # Examples
+    ///
+    /// A dense layer followed by a sigmoid agrees with finite differences:
+    /// ```rust
+    /// use nn_utils::network::{DenseLayer, Sigmoid};
+    ///
+    /// let dense = |w: [f32; 2]| DenseLayer::<2, 1>::from_parts(Box::new([w]), Box::new([0.1]));
+    /// let f = |layer: &DenseLayer<2, 1>, x: &[f32; 2]| {
+    ///     let (mut z, mut y) = ([0.0], [0.0]);
+    ///     layer.forward(x, &mut z);
+    ///     Sigmoid::<1>::init().forward(&z, &mut y);
+    ///     y[0]
+    /// };
+    ///
+    /// let (w, x, h) = ([0.3, -0.5], [1.5, 2.0], 1e-3);
+    /// let mut layer = dense(w);
+    ///
+    /// let (mut z, mut delta, mut grad_x) = ([0.0], [0.0], [0.0; 2]);
+    /// layer.forward(&x, &mut z);
+    /// Sigmoid::<1>::init().backward(&z, &[1.0], &mut delta);
+    /// layer.backward(&x, &delta, &mut grad_x);
+    ///
+    /// for i in 0..2 {
+    ///     let (mut lo, mut hi) = (w, w);
+    ///     lo[i] -= h;
+    ///     hi[i] += h;
+    ///     let numeric = (f(&dense(hi), &x) - f(&dense(lo), &x)) / (2.0 * h);
+    ///     assert!((numeric - layer.weight_grads()[0][i]).abs() < 1e-3);
+    ///
+    ///     let (mut lo, mut hi) = (x, x);
+    ///     lo[i] -= h;
+    ///     hi[i] += h;
+    ///     let numeric = (f(&layer, &hi) - f(&layer, &lo)) / (2.0 * h);
+    ///     assert!((numeric - grad_x[i]).abs() < 1e-3);
+    /// }
+    /// ```
+    pub fn backward(&mut self, input: &[f32], grad_output: &[f32], grad_input: &mut [f32]) {
+        grad_input[..IN].fill(0.0);
+
+        for (o, &go) in grad_output[..OUT].iter().enumerate() {
+            self.bias_grads[o] += go;
+
+            for ((w, wg), (x, g)) in self.weights[o]
+                .iter()
+                .zip(self.weight_grads[o].iter_mut())
+                .zip(input[..IN].iter().zip(grad_input[..IN].iter_mut()))
+            {
+                *wg += go * x;
+                *g += w * go;
             }
-            output[o] = sum;
         }
     }
+
+    pub fn weight_grads(&self) -> &[[f32; IN]; OUT] {
+        &self.weight_grads
+    }
+
+    pub fn bias_grads(&self) -> &[f32; OUT] {
+        &self.bias_grads
+    }
+
+    pub fn zero_grads(&mut self) {
+        self.weight_grads.as_flattened_mut().fill(0.0);
+        self.bias_grads.fill(0.0);
+    }
+}
+
+impl<const IN: usize, const OUT: usize> NetworkTrait<IN, OUT> for DenseLayer<IN, OUT> {
+    fn forward(&self, input: &[f32; IN]) -> [f32; OUT] {
+        let mut output = [0.0; OUT];
+        DenseLayer::forward(self, input, &mut output);
+        output
+    }
+
+    fn backward(&mut self, input: &[f32; IN], grad_output: &[f32; OUT]) {
+        // the gradient w.r.t. the input has nowhere further to go
+        let mut grad_input = [0.0; IN];
+        DenseLayer::backward(self, input, grad_output, &mut grad_input);
+    }
+
+    fn for_each_param(&mut self, f: &mut dyn FnMut(&mut [f32], &[f32])) {
+        f(
+            self.weights.as_flattened_mut(),
+            self.weight_grads.as_flattened(),
+        );
+        f(&mut self.biases[..], &self.bias_grads[..]);
+    }
+
+    fn zero_grads(&mut self) {
+        DenseLayer::zero_grads(self);
+    }
+}
+
+impl<const IW: usize, const IH: usize, const IC: usize> GlobalAvgPool<IW, IH, IC> {
+    pub fn init() -> Self {
+        GlobalAvgPool
+    }
+
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::{Tensor, network::GlobalAvgPool};
+    ///
+    /// // two 2x3 channels, one of 1.5s and one of -2s
+    /// let mut input = Tensor::fill(1.5);
+    /// input.as_mut_slice()[6..].fill(-2.0);
+    ///
+    /// let mut out = [0.0; 2];
+    /// GlobalAvgPool::<3, 2, 2>::init().forward(&input, &mut out);
+    /// assert_eq!(out, [1.5, -2.0]);
+    /// ```
+    pub fn forward(
+        &self,
+        input: &Tensor<{ IC * IH * IW }, 3, shape_ty!(IC, IH, IW), f32>,
+        out: &mut [f32; IC],
+    ) {
+        for (o, channel) in out.iter_mut().zip(input.as_slice().chunks_exact(IH * IW)) {
+            *o = channel.iter().sum::<f32>() / (IH * IW) as f32;
+        }
+    }
+
+    /// Spreads each channel's gradient evenly over its `IH * IW` positions
+    pub fn backward(
+        &self,
+        grad_output: &[f32; IC],
+        grad_input: &mut Tensor<{ IC * IH * IW }, 3, shape_ty!(IC, IH, IW), f32>,
+    ) {
+        for (go, channel) in grad_output
+            .iter()
+            .zip(grad_input.as_mut_slice().chunks_exact_mut(IH * IW))
+        {
+            channel.fill(go / (IH * IW) as f32);
+        }
+    }
+}
+
+// Pooling windows are `K x K`, moved by `S`, over each channel of a `(IC, IH, IW)` input, giving a
+// `(IC, OUT_H, OUT_W)` output. Both layers share the shape rules through this macro.
+macro_rules! impl_pool_shape {
+    ($($pool:ident),*) => {
+        $(
+            impl<const IW: usize, const IH: usize, const IC: usize, const K: usize, const S: usize>
+                $pool<IW, IH, IC, K, S>
+            {
+                // evaluated when `init` is monomorphized, so a bad configuration fails to compile
+                const VALID: () = {
+                    assert!(K >= 1 && S >= 1, "window and stride must be at least 1");
+                    assert!(K <= IH && K <= IW, "window is larger than the input");
+                };
+
+                /// Output height, `(IH - K) / S + 1`
+                pub const OUT_H: usize = (IH - K) / S + 1;
+                /// Output width, `(IW - K) / S + 1`
+                pub const OUT_W: usize = (IW - K) / S + 1;
+                /// Elements in the output, `IC * OUT_H * OUT_W`
+                pub const OUT_N: usize = IC * Self::OUT_H * Self::OUT_W;
+
+                pub fn init() -> Self {
+                    let () = Self::VALID;
+                    $pool
+                }
+
+                // flat input indices of the window under output position `(c, y, x)`
+                fn window(c: usize, y: usize, x: usize) -> impl Iterator<Item = usize> {
+                    (0..K).flat_map(move |ky| {
+                        (0..K).map(move |kx| (c * IH + y * S + ky) * IW + x * S + kx)
+                    })
+                }
+
+                // every output position as `(flat output index, c, y, x)`
+                fn positions() -> impl Iterator<Item = (usize, usize, usize, usize)> {
+                    (0..IC).flat_map(|c| {
+                        (0..Self::OUT_H).flat_map(move |y| {
+                            (0..Self::OUT_W)
+                                .map(move |x| ((c * Self::OUT_H + y) * Self::OUT_W + x, c, y, x))
+                        })
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_pool_shape!(MaxPool, AvgPool);
+
+impl<const IW: usize, const IH: usize, const IC: usize, const K: usize, const S: usize>
+    MaxPool<IW, IH, IC, K, S>
+{
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::network::MaxPool;
+    ///
+    /// let input: [f32; 16] = std::array::from_fn(|i| ((i * 7) % 16) as f32);
+    /// // 0  7 14  5
+    /// // 12 3 10  1
+    /// // 8 15  6 13
+    /// // 4 11  2  9
+    /// let pool = MaxPool::<4, 4, 1, 2, 2>::init();
+    /// let mut output = [0.0; 4];
+    /// pool.forward(&input, &mut output);
+    /// assert_eq!(output, [12.0, 14.0, 15.0, 13.0]);
+    ///
+    /// // each window's gradient goes to its maximum
+    /// let mut grad = [0.0; 16];
+    /// pool.backward(&input, &[1.0, 2.0, 3.0, 4.0], &mut grad);
+    /// assert_eq!((grad[4], grad[2], grad[9], grad[11]), (1.0, 2.0, 3.0, 4.0));
+    /// assert_eq!(grad.iter().sum::<f32>(), 10.0);
+    /// ```
+    pub fn forward(&self, input: &[f32], output: &mut [f32]) {
+        for (o, c, y, x) in Self::positions() {
+            output[o] = Self::window(c, y, x)
+                .map(|i| input[i])
+                .fold(f32::NEG_INFINITY, f32::max);
+        }
+    }
+
+    /// Routes each output's gradient to the first maximum of its window, accumulating where
+    /// windows overlap
+    pub fn backward(&self, input: &[f32], grad_output: &[f32], grad_input: &mut [f32]) {
+        grad_input[..IC * IH * IW].fill(0.0);
+
+        for (o, c, y, x) in Self::positions() {
+            let argmax = Self::window(c, y, x)
+                .reduce(|best, i| if input[i] > input[best] { i } else { best })
+                .expect("windows are never empty");
+            grad_input[argmax] += grad_output[o];
+        }
+    }
+}
+
+impl<const IW: usize, const IH: usize, const IC: usize, const K: usize, const S: usize>
+    AvgPool<IW, IH, IC, K, S>
+{
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::network::AvgPool;
+    ///
+    /// // 2x2 windows with stride 1 over a 3x3 channel
+    /// let input = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0];
+    /// let pool = AvgPool::<3, 3, 1, 2, 1>::init();
+    /// let mut output = [0.0; 4];
+    /// pool.forward(&input, &mut output);
+    /// assert_eq!(output, [3.0, 4.0, 6.0, 7.0]);
+    ///
+    /// // the center is in all four windows
+    /// let mut grad = [0.0; 9];
+    /// pool.backward(&[1.0; 4], &mut grad);
+    /// assert_eq!(grad, [0.25, 0.5, 0.25, 0.5, 1.0, 0.5, 0.25, 0.5, 0.25]);
+    /// ```
+    pub fn forward(&self, input: &[f32], output: &mut [f32]) {
+        for (o, c, y, x) in Self::positions() {
+            output[o] = Self::window(c, y, x).map(|i| input[i]).sum::<f32>() / (K * K) as f32;
+        }
+    }
+
+    /// Spreads each output's gradient evenly over its window, accumulating where windows overlap
+    pub fn backward(&self, grad_output: &[f32], grad_input: &mut [f32]) {
+        grad_input[..IC * IH * IW].fill(0.0);
+
+        for (o, c, y, x) in Self::positions() {
+            for i in Self::window(c, y, x) {
+                grad_input[i] += grad_output[o] / (K * K) as f32;
+            }
+        }
+    }
+}
+
+impl<const VOCAB: usize, const DIM: usize> Embedding<VOCAB, DIM> {
+    /// All-zero vectors
+    pub fn init() -> Self {
+        Self {
+            table: zeroed_table(),
+            grads: zeroed_table(),
+        }
+    }
+
+    /// Uniformly random vectors in `[0, 1)`
+    pub fn init_random() -> Self {
+        let mut embedding = Self::init();
+        rand::fill(embedding.table.as_flattened_mut());
+        embedding
+    }
+
+    /// One row of `DIM` values per index
+    pub fn table(&self) -> &[[f32; DIM]; VOCAB] {
+        &self.table
+    }
+
+    pub fn table_mut(&mut self) -> &mut [[f32; DIM]; VOCAB] {
+        &mut self.table
+    }
+
+    /// Copies the vector stored for `idx` into `out`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::network::{Embedding, OutOfVocab};
+    ///
+    /// let mut embedding = Embedding::<4, 2>::init();
+    /// embedding.table_mut()[2] = [0.5, -1.0];
+    ///
+    /// let mut out = [0.0; 2];
+    /// embedding.forward(2, &mut out).unwrap();
+    /// assert_eq!(out, [0.5, -1.0]);
+    ///
+    /// assert_eq!(embedding.forward(4, &mut out), Err(OutOfVocab { index: 4, vocab: 4 }));
+    /// ```
+    pub fn forward(&self, idx: usize, out: &mut [f32; DIM]) -> Result<(), OutOfVocab> {
+        *out = *self.row(idx)?;
+        Ok(())
+    }
+
+    /// Accumulates `grad_output` into the gradient of row `idx`, kept until
+    /// [`Embedding::zero_grads`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::network::Embedding;
+    ///
+    /// let mut embedding = Embedding::<3, 2>::init_random();
+    /// embedding.backward(1, &[1.0, 2.0]).unwrap();
+    /// embedding.backward(1, &[0.5, 0.5]).unwrap();
+    /// assert_eq!(embedding.grads(), &[[0.0; 2], [1.5, 2.5], [0.0; 2]]);
+    /// assert!(embedding.backward(3, &[1.0, 1.0]).is_err());
+    /// ```
+    pub fn backward(&mut self, idx: usize, grad_output: &[f32; DIM]) -> Result<(), OutOfVocab> {
+        self.row(idx)?;
+        for (g, go) in self.grads[idx].iter_mut().zip(grad_output) {
+            *g += go;
+        }
+        Ok(())
+    }
+
+    pub fn grads(&self) -> &[[f32; DIM]; VOCAB] {
+        &self.grads
+    }
+
+    pub fn zero_grads(&mut self) {
+        self.grads.as_flattened_mut().fill(0.0);
+    }
+
+    fn row(&self, idx: usize) -> Result<&[f32; DIM], OutOfVocab> {
+        self.table.get(idx).ok_or(OutOfVocab {
+            index: idx,
+            vocab: VOCAB,
+        })
+    }
+}
+
+// built through a `Vec`, as a large table would overflow the stack on its way into a `Box`
+fn zeroed_table<const VOCAB: usize, const DIM: usize>() -> Box<[[f32; DIM]; VOCAB]> {
+    vec![[0.0; DIM]; VOCAB]
+        .into_boxed_slice()
+        .try_into()
+        .expect("the vec has exactly VOCAB rows")
+}
+
+/// Error from [`Embedding`] when an index has no vector
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutOfVocab {
+    pub index: usize,
+    pub vocab: usize,
+}
+
+impl fmt::Display for OutOfVocab {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "index {} is out of range for a vocabulary of {}",
+            self.index, self.vocab
+        )
+    }
+}
+
+impl Error for OutOfVocab {}
+
+/// Error from a network's `forward_checked`: the first NaN or infinity, and the layer that produced it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NonFiniteError {
+    /// Index of the layer whose output it is
+    pub layer: usize,
+    /// Position within that layer's output
+    pub index: usize,
+    pub value: f32,
+}
+
+impl fmt::Display for NonFiniteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "layer {} produced {} at output {}",
+            self.layer, self.value, self.index
+        )
+    }
+}
+
+impl Error for NonFiniteError {}
+
+/// Errors on the first non-finite value in `output`, the output of layer `layer`
+///
+/// # Examples
+///
+/// ```rust
+/// use nn_utils::network::{NonFiniteError, check_finite};
+///
+/// assert_eq!(check_finite(0, &[1.0, -2.5, f32::MAX]), Ok(()));
+///
+/// let err = check_finite(3, &[0.0, f32::INFINITY, f32::NAN]).unwrap_err();
+/// assert_eq!((err.layer, err.index), (3, 1));
+/// assert_eq!(err.to_string(), "layer 3 produced inf at output 1");
+/// ```
+pub fn check_finite(layer: usize, output: &[f32]) -> Result<(), NonFiniteError> {
+    match output.iter().position(|v| !v.is_finite()) {
+        Some(index) => Err(NonFiniteError {
+            layer,
+            index,
+            value: output[index],
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Magic number at the start of a saved network
+pub const MAGIC: [u8; 4] = *b"nnw1";
+
+/// Reading and writing a layer's learnable parameters as little-endian `f32`s
+///
+/// Layers without parameters (activations) keep the default no-op implementations.
+pub trait Parameters {
+    fn save(&self, _w: &mut dyn Write) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn load(&mut self, _r: &mut dyn Read) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<const N: usize> Parameters for ReLU<N> {}
+
+impl<const N: usize> Parameters for Sigmoid<N> {}
+
+impl<const N: usize> Parameters for Tanh<N> {}
+
+impl<const N: usize> Parameters for LeakyReLU<N> {}
+
+impl<const N: usize> Parameters for ELU<N> {}
+
+impl<const N: usize> Parameters for SiLU<N> {}
+
+impl<const N: usize> Parameters for GELU<N> {}
+
+impl<const IW: usize, const IH: usize, const IC: usize> Parameters for GlobalAvgPool<IW, IH, IC> {}
+
+impl<const IW: usize, const IH: usize, const IC: usize, const K: usize, const S: usize> Parameters
+    for MaxPool<IW, IH, IC, K, S>
+{
+}
+
+impl<const IW: usize, const IH: usize, const IC: usize, const K: usize, const S: usize> Parameters
+    for AvgPool<IW, IH, IC, K, S>
+{
+}
+
+impl<const N: usize> Parameters for Softmax<N> {}
+
+impl<const N: usize> Parameters for LayerNorm<N> {
+    fn save(&self, w: &mut dyn Write) -> io::Result<()> {
+        write_f32s(w, &self.gamma)?;
+        write_f32s(w, &self.beta)
+    }
+
+    fn load(&mut self, r: &mut dyn Read) -> io::Result<()> {
+        read_f32s(r, &mut self.gamma)?;
+        read_f32s(r, &mut self.beta)
+    }
+}
+
+// running statistics are saved too, as evaluation depends on them
+impl<const N: usize> Parameters for BatchNorm<N> {
+    fn save(&self, w: &mut dyn Write) -> io::Result<()> {
+        write_f32s(w, &self.gamma)?;
+        write_f32s(w, &self.beta)?;
+        write_f32s(w, &self.running_mean)?;
+        write_f32s(w, &self.running_var)
+    }
+
+    fn load(&mut self, r: &mut dyn Read) -> io::Result<()> {
+        read_f32s(r, &mut self.gamma)?;
+        read_f32s(r, &mut self.beta)?;
+        read_f32s(r, &mut self.running_mean)?;
+        read_f32s(r, &mut self.running_var)
+    }
+}
+
+impl<const VOCAB: usize, const DIM: usize> Parameters for Embedding<VOCAB, DIM> {
+    fn save(&self, w: &mut dyn Write) -> io::Result<()> {
+        write_f32s(w, self.table.as_flattened())
+    }
+
+    fn load(&mut self, r: &mut dyn Read) -> io::Result<()> {
+        read_f32s(r, self.table.as_flattened_mut())
+    }
+}
+
+impl<const IN: usize, const OUT: usize> Parameters for DenseLayer<IN, OUT> {
+    fn save(&self, w: &mut dyn Write) -> io::Result<()> {
+        write_f32s(w, self.weights.as_flattened())?;
+        write_f32s(w, &self.biases[..])
+    }
+
+    fn load(&mut self, r: &mut dyn Read) -> io::Result<()> {
+        read_f32s(r, self.weights.as_flattened_mut())?;
+        read_f32s(r, &mut self.biases[..])
+    }
+}
+
+pub(crate) fn write_f32s(w: &mut dyn Write, values: &[f32]) -> io::Result<()> {
+    for v in values {
+        w.write_all(&v.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+pub(crate) fn read_f32s(r: &mut dyn Read, values: &mut [f32]) -> io::Result<()> {
+    let mut bytes = [0; 4];
+    for v in values {
+        r.read_exact(&mut bytes)?;
+        *v = f32::from_le_bytes(bytes);
+    }
+    Ok(())
+}
+
+fn read_u32(r: &mut dyn Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    r.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// Writes the header of a saved network: [`MAGIC`], the layer count, and each layer's `(input, output)` sizes
+pub fn write_header(w: &mut dyn Write, shapes: &[(usize, usize)]) -> io::Result<()> {
+    w.write_all(&MAGIC)?;
+    w.write_all(&(shapes.len() as u32).to_le_bytes())?;
+    for &(input, output) in shapes {
+        w.write_all(&(input as u32).to_le_bytes())?;
+        w.write_all(&(output as u32).to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Reads the header written by [`write_header`], erroring if it does not describe exactly `shapes`
+pub fn read_header(r: &mut dyn Read, shapes: &[(usize, usize)]) -> io::Result<()> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+    let mut magic = [0; 4];
+    r.read_exact(&mut magic)?;
+    if magic != MAGIC {
+        return Err(invalid(format!("bad magic number {magic:?}")));
+    }
+
+    let count = read_u32(r)? as usize;
+    if count != shapes.len() {
+        return Err(invalid(format!(
+            "expected {} layers, found {count}",
+            shapes.len()
+        )));
+    }
+
+    for (i, &expected) in shapes.iter().enumerate() {
+        let found = (read_u32(r)? as usize, read_u32(r)? as usize);
+        if found != expected {
+            return Err(invalid(format!(
+                "layer {i}: expected shape {expected:?}, found {found:?}"
+            )));
+        }
+    }
+
+    Ok(())
 }
@@ -0,0 +1,214 @@
+use std::{f32::consts::PI, fmt, sync::Arc};
+
+/// Adam optimizer (Kingma & Ba, 2014)
+///
+/// The first and second moment buffers (`m` and `v`) are owned by the caller so that they persist
+/// across steps and one `Adam` can drive several parameter groups.
+///
+/// # Examples
+///
+/// On a constant gradient, the bias-corrected step size is `lr` from the very first step:
+/// ```rust
+/// use nn_utils::optim::Adam;
+///
+/// let mut adam = Adam::new(0.01);
+/// let mut params = [0.0f32; 3];
+/// let (mut m, mut v) = ([0.0; 3], [0.0; 3]);
+///
+/// for _ in 0..10 {
+///     let before = params;
+///     adam.step(&mut params, &[0.5, -2.0, 10.0], &mut m, &mut v);
+///
+///     for (b, p) in before.iter().zip(&params) {
+///         assert!(((b - p).abs() - 0.01).abs() < 1e-6);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Adam {
+    pub lr: f32,
+    pub beta1: f32,
+    pub beta2: f32,
+    pub eps: f32,
+    /// Number of steps taken so far
+    pub t: i32,
+    schedule: Option<Arc<dyn LrSchedule>>,
+}
+
+impl Adam {
+    /// Creates an optimizer with the reference hyperparameters (`beta1 = 0.9`, `beta2 = 0.999`, `eps = 1e-8`)
+    pub fn new(lr: f32) -> Self {
+        Self {
+            lr,
+            beta1: 0.9,
+            beta2: 0.999,
+            eps: 1e-8,
+            t: 0,
+            schedule: None,
+        }
+    }
+
+    /// Takes the learning rate from `schedule` at every step instead of the fixed `lr`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::optim::{Adam, StepDecay};
+    ///
+    /// let mut adam = Adam::new(1.0).with_schedule(StepDecay { base: 0.01, gamma: 0.5, step_size: 2 });
+    /// assert_eq!(adam.current_lr(), 0.01);
+    ///
+    /// let (mut params, mut m, mut v) = ([0.0f32], [0.0], [0.0]);
+    /// for _ in 0..2 {
+    ///     adam.step(&mut params, &[1.0], &mut m, &mut v);
+    /// }
+    /// assert_eq!(adam.current_lr(), 0.005);
+    /// assert!((params[0] + 0.02).abs() < 1e-6);
+    /// ```
+    pub fn with_schedule(mut self, schedule: impl LrSchedule + 'static) -> Self {
+        self.schedule = Some(Arc::new(schedule));
+        self
+    }
+
+    /// The learning rate the next step will use
+    pub fn current_lr(&self) -> f32 {
+        self.schedule
+            .as_ref()
+            .map_or(self.lr, |s| s.lr(self.t as usize))
+    }
+
+    pub fn step(&mut self, params: &mut [f32], grads: &[f32], m: &mut [f32], v: &mut [f32]) {
+        assert_eq!(params.len(), grads.len());
+        assert_eq!(params.len(), m.len());
+        assert_eq!(params.len(), v.len());
+
+        let lr = self.current_lr();
+        self.t += 1;
+
+        // bias corrections for the zero-initialized moments
+        let c1 = 1.0 - self.beta1.powi(self.t);
+        let c2 = 1.0 - self.beta2.powi(self.t);
+
+        for (((p, g), m), v) in params.iter_mut().zip(grads).zip(m).zip(v) {
+            *m = self.beta1 * *m + (1.0 - self.beta1) * g;
+            *v = self.beta2 * *v + (1.0 - self.beta2) * g * g;
+
+            let m_hat = *m / c1;
+            let v_hat = *v / c2;
+
+            *p -= lr * m_hat / (v_hat.sqrt() + self.eps);
+        }
+    }
+}
+
+/// Rescales `grads` so their global L2 norm is at most `max_norm`, returning the norm before clipping
+///
+/// # Examples
+///
+/// ```rust
+/// use nn_utils::optim::clip_grad_norm;
+///
+/// let mut grads = [6.0, 8.0];
+/// assert_eq!(clip_grad_norm(&mut grads, 1.0), 10.0);
+/// assert_eq!(grads, [0.6, 0.8]);
+///
+/// // already within bounds, so left alone
+/// assert_eq!(clip_grad_norm(&mut grads, 5.0), 1.0);
+/// assert_eq!(grads, [0.6, 0.8]);
+/// ```
+pub fn clip_grad_norm(grads: &mut [f32], max_norm: f32) -> f32 {
+    let norm = grads.iter().map(|g| g * g).sum::<f32>().sqrt();
+
+    if norm > max_norm {
+        let scale = max_norm / norm;
+        for g in grads {
+            *g *= scale;
+        }
+    }
+
+    norm
+}
+
+/// Clamps every gradient to `[-clip, clip]`
+///
+/// # Examples
+///
+/// ```rust
+/// use nn_utils::optim::clip_grad_value;
+///
+/// let mut grads = [-3.0, 0.5, 2.0];
+/// clip_grad_value(&mut grads, 1.0);
+/// assert_eq!(grads, [-1.0, 0.5, 1.0]);
+/// ```
+pub fn clip_grad_value(grads: &mut [f32], clip: f32) {
+    for g in grads {
+        *g = g.clamp(-clip, clip);
+    }
+}
+
+/// A learning rate that varies over training
+pub trait LrSchedule: fmt::Debug {
+    /// Learning rate for the zero-based `step`
+    fn lr(&self, step: usize) -> f32;
+}
+
+/// Multiplies the learning rate by `gamma` every `step_size` steps, or never if `step_size` is 0
+///
+/// # Examples
+///
+/// ```rust
+/// use nn_utils::optim::{LrSchedule, StepDecay};
+///
+/// let schedule = StepDecay { base: 0.1, gamma: 0.5, step_size: 10 };
+/// assert_eq!(schedule.lr(0), 0.1);
+/// assert_eq!(schedule.lr(9), 0.1);
+/// assert_eq!(schedule.lr(10), 0.05);
+/// assert_eq!(schedule.lr(25), 0.025);
+///
+/// let constant = StepDecay { step_size: 0, ..schedule };
+/// assert_eq!(constant.lr(25), 0.1);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct StepDecay {
+    pub base: f32,
+    pub gamma: f32,
+    pub step_size: usize,
+}
+
+impl LrSchedule for StepDecay {
+    fn lr(&self, step: usize) -> f32 {
+        let decays = step.checked_div(self.step_size).unwrap_or(0);
+        self.base * self.gamma.powi(decays as i32)
+    }
+}
+
+/// Anneals from `base` down to `min` along half a cosine over `total_steps`, then stays at `min`
+///
+/// # Examples
+///
+/// ```rust
+/// use nn_utils::optim::{CosineAnnealing, LrSchedule};
+///
+/// let schedule = CosineAnnealing { base: 0.1, min: 0.001, total_steps: 100 };
+/// assert_eq!(schedule.lr(0), 0.1);
+/// assert!((schedule.lr(50) - 0.0505).abs() < 1e-6);
+/// assert_eq!(schedule.lr(100), 0.001);
+/// assert_eq!(schedule.lr(1000), 0.001);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct CosineAnnealing {
+    pub base: f32,
+    pub min: f32,
+    pub total_steps: usize,
+}
+
+impl LrSchedule for CosineAnnealing {
+    fn lr(&self, step: usize) -> f32 {
+        if step >= self.total_steps {
+            return self.min;
+        }
+
+        let progress = step as f32 / self.total_steps as f32;
+        self.min + (self.base - self.min) * (1.0 + (PI * progress).cos()) / 2.0
+    }
+}
@@ -0,0 +1,289 @@
+use crate::network::{
+    DenseLayer, ELU, GELU, LayerNorm, LeakyReLU, ReLU, SiLU, Sigmoid, Softmax, Tanh,
+};
+use std::{error::Error, fmt};
+
+/// Object-safe view of a layer, so differently typed layers can share one container
+pub trait Layer: fmt::Debug {
+    fn input_size(&self) -> usize;
+    fn output_size(&self) -> usize;
+
+    /// Overwrites `output` with this layer's `output_size()` activations
+    fn forward(&self, input: &[f32], output: &mut Vec<f32>);
+
+    /// Overwrites `grad_input` with the loss gradient w.r.t. `input`, given the gradient
+    /// w.r.t. this layer's output on `input`
+    ///
+    /// Layers with trainable parameters also accumulate their gradients, as their own `backward`
+    /// does.
+    fn backward(&mut self, input: &[f32], grad_output: &[f32], grad_input: &mut Vec<f32>);
+
+    /// Feeds this layer's output into `next`
+    ///
+    /// Panics if `next` doesn't take this layer's `output_size()` inputs.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::network::{ReLU, Sigmoid};
+    /// use nn_utils::sequential::Layer;
+    ///
+    /// let mut layer = ReLU::<1>::init().then(Sigmoid::<1>::init());
+    ///
+    /// let mut out = Vec::new();
+    /// layer.forward(&[-2.0], &mut out);
+    /// assert_eq!(out, [0.5]);
+    ///
+    /// layer.forward(&[2.0], &mut out);
+    /// let s = 1.0 / (1.0 + (-2f32).exp());
+    /// assert_eq!(out, [s]);
+    ///
+    /// // sigmoid'(relu(x)) * relu'(x), which is zero for negative inputs
+    /// let mut grad = Vec::new();
+    /// layer.backward(&[2.0], &[1.0], &mut grad);
+    /// assert_eq!(grad, [s * (1.0 - s)]);
+    ///
+    /// layer.backward(&[-2.0], &[1.0], &mut grad);
+    /// assert_eq!(grad, [0.0]);
+    /// ```
+    fn then<B: Layer>(self, next: B) -> Chain<Self, B>
+    where
+        Self: Sized,
+    {
+        Chain::new(self, next)
+    }
+}
+
+/// Two layers run one after the other, built with [`Layer::then`]
+///
+/// A typed alternative to [`Sequential`] for small, fixed pipelines.
+#[derive(Debug)]
+pub struct Chain<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A: Layer, B: Layer> Chain<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        assert_eq!(
+            first.output_size(),
+            second.input_size(),
+            "chained layers must agree on the size between them"
+        );
+        Self { first, second }
+    }
+
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+}
+
+impl<A: Layer, B: Layer> Layer for Chain<A, B> {
+    fn input_size(&self) -> usize {
+        self.first.input_size()
+    }
+
+    fn output_size(&self) -> usize {
+        self.second.output_size()
+    }
+
+    fn forward(&self, input: &[f32], output: &mut Vec<f32>) {
+        let mut hidden = Vec::with_capacity(self.first.output_size());
+        self.first.forward(input, &mut hidden);
+        self.second.forward(&hidden, output);
+    }
+
+    // in reverse: `second` needs the activations between the layers, so they're recomputed
+    fn backward(&mut self, input: &[f32], grad_output: &[f32], grad_input: &mut Vec<f32>) {
+        let mut hidden = Vec::with_capacity(self.first.output_size());
+        self.first.forward(input, &mut hidden);
+
+        let mut grad_hidden = Vec::with_capacity(hidden.len());
+        self.second.backward(&hidden, grad_output, &mut grad_hidden);
+        self.first.backward(input, &grad_hidden, grad_input);
+    }
+}
+
+impl<const IN: usize, const OUT: usize> Layer for DenseLayer<IN, OUT> {
+    fn input_size(&self) -> usize {
+        IN
+    }
+
+    fn output_size(&self) -> usize {
+        OUT
+    }
+
+    fn forward(&self, input: &[f32], output: &mut Vec<f32>) {
+        output.resize(OUT, 0.0);
+        DenseLayer::forward(self, input, output);
+    }
+
+    fn backward(&mut self, input: &[f32], grad_output: &[f32], grad_input: &mut Vec<f32>) {
+        grad_input.resize(IN, 0.0);
+        DenseLayer::backward(self, input, grad_output, grad_input);
+    }
+}
+
+// activations (and normalization) map `N` inputs to `N` outputs
+macro_rules! impl_activation_layer {
+    ($($layer:ident),*) => {
+        $(
+            impl<const N: usize> Layer for $layer<N> {
+                fn input_size(&self) -> usize {
+                    N
+                }
+
+                fn output_size(&self) -> usize {
+                    N
+                }
+
+                fn forward(&self, input: &[f32], output: &mut Vec<f32>) {
+                    output.resize(N, 0.0);
+                    $layer::forward(self, input, output);
+                }
+
+                fn backward(
+                    &mut self,
+                    input: &[f32],
+                    grad_output: &[f32],
+                    grad_input: &mut Vec<f32>,
+                ) {
+                    grad_input.resize(N, 0.0);
+                    $layer::backward(self, input, grad_output, grad_input);
+                }
+            }
+        )*
+    };
+}
+
+impl_activation_layer!(
+    ReLU, Sigmoid, Tanh, LeakyReLU, ELU, SiLU, GELU, Softmax, LayerNorm
+);
+
+/// A stack of layers assembled at runtime
+///
+/// Where `network!` fixes the architecture in its type, a `Sequential` can be built from whatever
+/// layers are at hand, with sizes checked as each one is pushed.
+///
+/// # Examples
+///
+/// ```rust
+/// use nn_utils::network::{DenseLayer, ReLU, Sigmoid};
+/// use nn_utils::sequential::{Sequential, SizeMismatch};
+///
+/// let net = Sequential::new()
+///     .push(DenseLayer::<3, 4>::init())?
+///     .push(ReLU::<4>::init())?
+///     .push(DenseLayer::<4, 2>::init())?;
+///
+/// assert_eq!((net.input_size(), net.output_size()), (Some(3), Some(2)));
+/// assert_eq!(net.forward(&[1.0, 2.0, 3.0]), vec![0.0, 0.0]);
+///
+/// let err = net.push(Sigmoid::<5>::init()).unwrap_err();
+/// assert_eq!(err, SizeMismatch { layer: 3, expected: 2, found: 5 });
+/// # Ok::<(), SizeMismatch>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct Sequential {
+    layers: Vec<Box<dyn Layer>>,
+}
+
+impl Sequential {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `layer`, erroring if its input size differs from the current output size
+    ///
+    /// A mismatch drops the stack; use [`Sequential::try_push`] to keep it.
+    pub fn push(mut self, layer: impl Layer + 'static) -> Result<Self, SizeMismatch> {
+        self.try_push(layer)?;
+        Ok(self)
+    }
+
+    /// Like [`Sequential::push`], but in place, so the stack survives a rejected layer
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::network::{DenseLayer, ReLU};
+    /// use nn_utils::sequential::Sequential;
+    ///
+    /// let mut net = Sequential::new();
+    /// net.try_push(DenseLayer::<3, 4>::init()).unwrap();
+    ///
+    /// // try candidates until one fits
+    /// assert!(net.try_push(ReLU::<5>::init()).is_err());
+    /// assert!(net.try_push(ReLU::<4>::init()).is_ok());
+    /// assert_eq!(net.layers().len(), 2);
+    /// ```
+    pub fn try_push(&mut self, layer: impl Layer + 'static) -> Result<(), SizeMismatch> {
+        if let Some(expected) = self.output_size() {
+            let found = layer.input_size();
+            if found != expected {
+                return Err(SizeMismatch {
+                    layer: self.layers.len(),
+                    expected,
+                    found,
+                });
+            }
+        }
+
+        self.layers.push(Box::new(layer));
+        Ok(())
+    }
+
+    pub fn layers(&self) -> &[Box<dyn Layer>] {
+        &self.layers
+    }
+
+    /// `None` until the first layer is pushed
+    pub fn input_size(&self) -> Option<usize> {
+        self.layers.first().map(|l| l.input_size())
+    }
+
+    pub fn output_size(&self) -> Option<usize> {
+        self.layers.last().map(|l| l.output_size())
+    }
+
+    pub fn forward(&self, input: &[f32]) -> Vec<f32> {
+        if let Some(expected) = self.input_size() {
+            assert_eq!(input.len(), expected, "wrong input size");
+        }
+
+        // ping-pong between two buffers instead of allocating per layer
+        let (mut current, mut next) = (input.to_vec(), Vec::new());
+        for layer in &self.layers {
+            layer.forward(&current, &mut next);
+            std::mem::swap(&mut current, &mut next);
+        }
+        current
+    }
+}
+
+/// Error from [`Sequential::push`] when a layer doesn't fit the one before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeMismatch {
+    /// Index the layer would have had
+    pub layer: usize,
+    /// Output size of the previous layer
+    pub expected: usize,
+    /// Input size of the rejected layer
+    pub found: usize,
+}
+
+impl fmt::Display for SizeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "layer {} takes {} inputs, but the previous layer outputs {}",
+            self.layer, self.found, self.expected
+        )
+    }
+}
+
+impl Error for SizeMismatch {}
@@ -1,19 +1,81 @@
 #[rustfmt::skip]
 use std::{
-    intrinsics::transmute_unchecked,
+    array,
+    error::Error,
+    fmt,
+    iter,
     marker::PhantomData,
-    ops,
-    ptr,
+    ops::{self, Range},
 };
 
+/// Element types a [`Tensor`] can hold
+pub trait Scalar:
+    Copy
+    + Default
+    + PartialOrd
+    + fmt::Debug
+    + fmt::Display
+    + iter::Sum
+    + ops::Add<Output = Self>
+    + ops::Sub<Output = Self>
+    + ops::Mul<Output = Self>
+    + ops::Div<Output = Self>
+    + ops::Neg<Output = Self>
+    + ops::AddAssign
+    + ops::SubAssign
+    + ops::MulAssign
+    + ops::DivAssign
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn abs(self) -> Self;
+    fn sqrt(self) -> Self;
+}
+
+macro_rules! impl_scalar {
+    ($($t:ty),*) => {
+        $(
+            impl Scalar for $t {
+                const ZERO: Self = 0.;
+                const ONE: Self = 1.;
+
+                fn abs(self) -> Self {
+                    <$t>::abs(self)
+                }
+
+                fn sqrt(self) -> Self {
+                    <$t>::sqrt(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_scalar!(f32, f64);
+
+/// A tensor of `N` elements of type `T` laid out as `Shape`
+///
+/// `Shape` is only a marker describing the dims (see [`shape_ty!`]), so it is built from `f64`
+/// arrays whatever the element type.
+///
+/// # Examples
+///
+/// ```rust
+/// use nn_utils::{Tensor, shape_ty};
+///
+/// let t = Tensor::<6, 2, shape_ty!(2, 3), f32>::fill(0.5);
+/// let x: f32 = *t.at([1, 2]);
+/// assert_eq!(x, 0.5);
+/// ```
 #[derive(Debug, Clone)]
-pub struct Tensor<const N: usize, const D: usize, Shape> {
-    pub(crate) data: Box<[f64; N]>,
+pub struct Tensor<const N: usize, const D: usize, Shape, T = f64> {
+    pub(crate) data: Box<[T; N]>,
     pub(crate) _shape_marker: PhantomData<Shape>,
 }
 
-impl<const N: usize, const D: usize> From<[f64; N]> for Tensor<N, D, [f64; N]> {
-    fn from(value: [f64; N]) -> Self {
+impl<const N: usize, T> From<[T; N]> for Tensor<N, 1, [f64; N], T> {
+    fn from(value: [T; N]) -> Self {
         Tensor {
             data: Box::new(value),
             _shape_marker: PhantomData,
@@ -21,29 +83,92 @@ impl<const N: usize, const D: usize> From<[f64; N]> for Tensor<N, D, [f64; N]> {
     }
 }
 
-impl<const N: usize, const D: usize, Shape> Tensor<N, D, Shape>
+impl<const N: usize, const D: usize, Shape, T: Scalar> Tensor<N, D, Shape, T>
 where
     Shape: ops::Index<usize>,
     <Shape as ops::Index<usize>>::Output: Sized + ArraySize,
 {
     pub fn new() -> Self {
+        Self::fill(T::ZERO)
+    }
+
+    pub fn zeros() -> Self {
+        Self::fill(T::ZERO)
+    }
+
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::{Tensor, shape_ty};
+    ///
+    /// let t = Tensor::<6, 2, shape_ty!(2, 3)>::ones();
+    /// for i in 0..2 {
+    ///     for j in 0..3 {
+    ///         assert_eq!(*t.at([i, j]), 1.);
+    ///     }
+    /// }
+    /// ```
+    pub fn ones() -> Self {
+        Self::fill(T::ONE)
+    }
+
+    /// A tensor with every element set to `value`
+    pub fn fill(value: T) -> Self {
         Self {
-            data: Box::new([0.; N]),
+            data: Box::new([value; N]),
             _shape_marker: PhantomData,
         }
     }
 
-    pub fn reshape<AltShp>(self) -> Tensor<N, D, AltShp>
+    /// A tensor of uniformly random elements in `[0, 1)`
+    pub fn random() -> Self
+    where
+        [T]: rand::Fill,
+    {
+        let mut t = Self::zeros();
+        rand::fill(&mut t.data[..]);
+        t
+    }
+
+    /// Reinterprets the buffer as `AltShp`, which must describe exactly `N` elements
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::{Tensor, shape_ty};
+    ///
+    /// let flat = Tensor::from([1.; 6]);
+    /// let t: Tensor<6, 2, shape_ty!(2, 3)> = flat.clone().try_reshape().unwrap();
+    /// assert_eq!(*t.at([1, 2]), 1.);
+    ///
+    /// let err = flat.try_reshape::<2, shape_ty!(2, 2)>().unwrap_err();
+    /// assert_eq!((err.expected, err.found), (6, 4));
+    /// ```
+    pub fn try_reshape<const D2: usize, AltShp>(
+        self,
+    ) -> Result<Tensor<N, D2, AltShp, T>, ReshapeError>
     where
-        Tensor<N, D, AltShp>: Sized,
+        AltShp: ShapeDims<D2>,
     {
-        assert_eq!(size_of::<AltShp>(), N * size_of::<f64>());
+        let found = AltShp::DIMS.iter().product();
+        if found != N {
+            return Err(ReshapeError { expected: N, found });
+        }
+
         let Tensor { data, .. } = self;
 
-        Tensor {
+        Ok(Tensor {
             data,
             _shape_marker: PhantomData::<AltShp>,
-        }
+        })
+    }
+
+    /// Panicking version of [`Tensor::try_reshape`]
+    pub fn reshape<const D2: usize, AltShp>(self) -> Tensor<N, D2, AltShp, T>
+    where
+        AltShp: ShapeDims<D2>,
+    {
+        self.try_reshape().unwrap_or_else(|e| panic!("{e}"))
     }
 
     pub fn get(
@@ -53,99 +178,944 @@ where
         { <<Shape as ops::Index<usize>>::Output as ArraySize>::SIZE },
         { D - 1 },
         <Shape as ops::Index<usize>>::Output,
+        T,
     >
     where
-        <Shape as ops::Index<usize>>::Output: Sized,
+        Shape: ShapeDims<D>,
     {
-        unsafe {
-            let t_data = &transmute_unchecked::<&[f64; N], &Shape>(&*self.data)[index];
+        let sub = self.view().axis(index);
+
+        Tensor {
+            data: Box::<[T]>::from(sub.data)
+                .try_into()
+                .expect("axis views have exactly `SIZE` elements"),
+            _shape_marker: PhantomData,
+        }
+    }
+
+    /// # Examples
+    ///
+    /// Elements are laid out in row-major order:
+    /// ```rust
+    /// let mut t = nn_utils::tensor!(2, 3);
+    /// t.set([1, 2], 5.);
+    /// assert_eq!(*t.at([1, 2]), 5.);
+    /// assert_eq!(t.view().axis(1)[[2]], 5.);
+    ///
+    /// let mut t = nn_utils::tensor!(2, 3, 4);
+    /// t.set([1, 0, 3], 8.);
+    /// assert_eq!(*t.at([1, 0, 3]), 8.);
+    /// assert_eq!(*t.at([0, 1, 3]), 0.);
+    /// ```
+    pub fn at(&self, index: [usize; D]) -> &T
+    where
+        Shape: ShapeDims<D>,
+    {
+        &self.data[Shape::flat_index(index)]
+    }
+
+    pub fn set(&mut self, index: [usize; D], value: T)
+    where
+        Shape: ShapeDims<D>,
+    {
+        self.data[Shape::flat_index(index)] = value;
+    }
+
+    /// The size of each axis, outermost first
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let t = nn_utils::tensor!(2, 3, 4);
+    /// assert_eq!(t.dims(), [2, 3, 4]);
+    /// assert_eq!(t.len(), 24);
+    /// ```
+    pub fn dims(&self) -> [usize; D]
+    where
+        Shape: ShapeDims<D>,
+    {
+        Shape::DIMS
+    }
+
+    /// Total number of elements, `N`
+    pub fn len(&self) -> usize {
+        N
+    }
+
+    pub fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    /// The elements in row-major order
+    pub fn as_slice(&self) -> &[T] {
+        &self.data[..]
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data[..]
+    }
+
+    /// Borrows the tensor as a [`TensorView`]
+    pub fn view(&self) -> TensorView<'_, N, D, Shape, T>
+    where
+        Shape: ShapeDims<D>,
+    {
+        TensorView {
+            data: &self.data[..],
+            strides: Shape::strides(),
+            _shape_marker: PhantomData,
+        }
+    }
+
+    /// Copies the elements into a new buffer with the axes reordered so that output axis `k` is input axis `order[k]`
+    ///
+    /// The permuted shape generally differs from `Shape`, so the result carries its dims at runtime.
+    ///
+    /// # Examples
+    ///
+    /// A 2-D transpose:
+    /// ```rust
+    /// use nn_utils::{Tensor, shape_ty};
+    ///
+    /// let t: Tensor<6, 2, shape_ty!(2, 3)> = Tensor::from([1., 2., 3., 4., 5., 6.]).reshape();
+    /// let transposed = t.permute([1, 0]);
+    ///
+    /// assert_eq!(transposed.dims(), [3, 2]);
+    /// assert_eq!(transposed.as_slice(), &[1., 4., 2., 5., 3., 6.]);
+    /// assert_eq!(transposed.at([2, 1]), *t.at([1, 2]));
+    /// ```
+    pub fn permute(&self, order: [usize; D]) -> DynTensor<D, T>
+    where
+        Shape: ShapeDims<D>,
+    {
+        let mut seen = [false; D];
+        for &axis in &order {
+            assert!(
+                axis < D && !seen[axis],
+                "{order:?} is not a permutation of 0..{D}"
+            );
+            seen[axis] = true;
+        }
 
-            let ptr = t_data as *const _ as *const f64;
-            let data_arr: [f64; <<Shape as ops::Index<usize>>::Output as ArraySize>::SIZE] =
-                ptr::read(ptr as *const _);
+        let in_strides = Shape::strides();
+        let dims = order.map(|axis| Shape::DIMS[axis]);
+        // stride in the source buffer for a step along each output axis
+        let strides = order.map(|axis| in_strides[axis]);
 
-            Tensor {
-                data: Box::new(data_arr),
-                _shape_marker: PhantomData,
+        let mut data = Vec::with_capacity(N);
+        let mut index = [0; D];
+        for _ in 0..N {
+            let flat: usize = index.iter().zip(&strides).map(|(i, s)| i * s).sum();
+            data.push(self.data[flat]);
+
+            // advance the row-major output index
+            for axis in (0..D).rev() {
+                index[axis] += 1;
+                if index[axis] < dims[axis] {
+                    break;
+                }
+                index[axis] = 0;
             }
         }
+
+        DynTensor { data, dims }
     }
 
-    pub fn at(&self, index: [usize; D]) -> &f64
+    /// Joins tensors along `axis` into a new buffer
+    ///
+    /// All inputs share `Shape`, so only the axis and the input count need validating. See
+    /// [`DynTensor::concat`] for joining tensors whose sizes along `axis` differ.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::{Tensor, shape_ty};
+    ///
+    /// let a: Tensor<6, 2, shape_ty!(2, 3)> = Tensor::from([1., 2., 3., 4., 5., 6.]).reshape();
+    /// let b = Tensor::fill(0.);
+    ///
+    /// let joined = Tensor::concat(&[&a, &b], 1).unwrap();
+    /// assert_eq!(joined.dims(), [2, 6]);
+    /// assert_eq!(joined.as_slice(), &[1., 2., 3., 0., 0., 0., 4., 5., 6., 0., 0., 0.]);
+    ///
+    /// assert!(Tensor::concat(&[&a, &b], 2).is_err());
+    /// ```
+    pub fn concat(tensors: &[&Self], axis: usize) -> Result<DynTensor<D, T>, ConcatError>
     where
-        Shape: GetFromIndex<D>,
+        Shape: ShapeDims<D>,
     {
-        unsafe { transmute_unchecked::<&[f64; N], &Shape>(&*self.data) }.at(index)
+        concat_parts(tensors.iter().map(|t| (&t.data[..], Shape::DIMS)), axis)
     }
 
-    pub fn set(&mut self, index: [usize; D], value: f64)
+    /// Copies the elements whose index along `axis` lies in `range` into a new buffer
+    ///
+    /// Panicking version of [`Tensor::try_slice`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let t = nn_utils::tensor![[1., 2., 3.], [4., 5., 6.], [7., 8., 9.], [10., 11., 12.]];
+    ///
+    /// let rows = t.slice(0, 1..3);
+    /// assert_eq!(rows.dims(), [2, 3]);
+    /// assert_eq!(rows.as_slice(), &[4., 5., 6., 7., 8., 9.]);
+    ///
+    /// let column = t.slice(1, 2..3);
+    /// assert_eq!(column.dims(), [4, 1]);
+    /// assert_eq!(column.as_slice(), &[3., 6., 9., 12.]);
+    /// ```
+    pub fn slice(&self, axis: usize, range: Range<usize>) -> DynTensor<D, T>
     where
-        Shape: GetFromIndexMut<D>,
+        Shape: ShapeDims<D>,
     {
-        *(unsafe { transmute_unchecked::<&mut [f64; N], &mut Shape>(&mut *self.data) }
-            .at_mut(index)) = value;
+        self.try_slice(axis, range)
+            .unwrap_or_else(|e| panic!("{e}"))
     }
 
-    pub fn slice<T: Iterator>(range: T) {
-        todo!()
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::SliceError;
+    ///
+    /// let t = nn_utils::tensor!(4, 3);
+    /// assert!(t.try_slice(0, 2..4).is_ok());
+    /// assert_eq!(t.try_slice(0, 2..5), Err(SliceError::RangeOutOfBounds { range: 2..5, len: 4 }));
+    /// assert_eq!(t.try_slice(2, 0..1), Err(SliceError::AxisOutOfBounds { axis: 2, rank: 2 }));
+    /// ```
+    pub fn try_slice(&self, axis: usize, range: Range<usize>) -> Result<DynTensor<D, T>, SliceError>
+    where
+        Shape: ShapeDims<D>,
+    {
+        if axis >= D {
+            return Err(SliceError::AxisOutOfBounds { axis, rank: D });
+        }
+
+        let len = Shape::DIMS[axis];
+        if range.start > range.end || range.end > len {
+            return Err(SliceError::RangeOutOfBounds { range, len });
+        }
+
+        // each index before `axis` selects a block of `len * inner` elements, of which a
+        // contiguous run is kept
+        let inner: usize = Shape::DIMS[axis + 1..].iter().product();
+        let block_len = (len * inner).max(1);
+        let mut data = Vec::with_capacity(N / block_len * range.len() * inner);
+        for block in self.data.chunks_exact(block_len) {
+            data.extend_from_slice(&block[range.start * inner..range.end * inner]);
+        }
+
+        let mut dims = Shape::DIMS;
+        dims[axis] = range.len();
+        Ok(DynTensor { data, dims })
     }
 }
 
-pub trait GetFromIndex<const N: usize> {
-    fn at(&self, index: [usize; N]) -> &f64;
+/// Error from [`Tensor::try_slice`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SliceError {
+    AxisOutOfBounds {
+        axis: usize,
+        rank: usize,
+    },
+    /// `range` isn't within the `len` indices of the axis
+    RangeOutOfBounds {
+        range: Range<usize>,
+        len: usize,
+    },
 }
 
-impl GetFromIndex<0> for f64 {
-    fn at(&self, _index: [usize; 0]) -> &f64 {
-        self
+impl fmt::Display for SliceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SliceError::AxisOutOfBounds { axis, rank } => {
+                write!(
+                    f,
+                    "axis {axis} is out of bounds for a tensor of rank {rank}"
+                )
+            }
+            SliceError::RangeOutOfBounds { range, len } => {
+                write!(
+                    f,
+                    "range {range:?} is out of bounds for an axis of length {len}"
+                )
+            }
+        }
     }
 }
 
-// Recursive case: nested arrays
-impl<T, const M: usize, const N: usize> GetFromIndex<N> for [T; M]
+impl Error for SliceError {}
+
+impl<const N: usize, const D: usize, Shape> Tensor<N, D, Shape>
 where
-    T: GetFromIndex<{ N - 1 }>,
+    Shape: ShapeDims<D> + ArraySize,
 {
-    default fn at(&self, index: [usize; N]) -> &f64 {
-        self[index[0]].at(core::array::from_fn(|i| index[i + 1]))
+    /// Copies a nested array whose type is the shape itself, e.g. a `[[f64; 3]; 2]` for
+    /// `shape_ty!(2, 3)`
+    ///
+    /// This is what the literal form of [`tensor!`] expands to.
+    pub fn from_nested(value: Shape) -> Self {
+        let mut data = Vec::with_capacity(N);
+        value.flatten_into(&mut data);
+
+        Tensor {
+            data: data
+                .into_boxed_slice()
+                .try_into()
+                .unwrap_or_else(|d: Box<[f64]>| panic!("expected {N} elements, found {}", d.len())),
+            _shape_marker: PhantomData,
+        }
+    }
+}
+
+// the layers compute in `f32` while `Tensor` defaults to `f64`; these are the one place to cross
+impl<const N: usize, const D: usize, Shape> Tensor<N, D, Shape> {
+    /// Narrows every element to `f32`, e.g. to feed a layer
+    ///
+    /// Precision drops to `f32`'s 24-bit mantissa (about 7 significant digits), and values beyond
+    /// `f32::MAX` become infinite.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::{Tensor, shape_ty};
+    ///
+    /// let t = Tensor::<6, 2, shape_ty!(2, 3)>::random();
+    /// let back = Tensor::<6, 2, shape_ty!(2, 3)>::from_f32_slice(&t.to_f32_vec());
+    /// for (&a, &b) in t.as_slice().iter().zip(back.as_slice()) {
+    ///     assert!((a - b).abs() <= f32::EPSILON as f64 * a.abs());
+    /// }
+    /// ```
+    pub fn to_f32_vec(&self) -> Vec<f32> {
+        self.data.iter().map(|&x| x as f32).collect()
+    }
+
+    /// Widens a layer's `f32` values into a tensor, which is exact
+    ///
+    /// Panics if `values` doesn't hold exactly `N` elements.
+    pub fn from_f32_slice(values: &[f32]) -> Self {
+        assert_eq!(
+            values.len(),
+            N,
+            "expected {N} elements, found {}",
+            values.len()
+        );
+
+        Tensor {
+            data: Box::new(array::from_fn(|i| f64::from(values[i]))),
+            _shape_marker: PhantomData,
+        }
+    }
+}
+
+impl<const N: usize, const D: usize, Shape, T: Scalar> Tensor<N, D, Shape, T> {
+    /// Whether every pair of corresponding elements differs by at most `tol`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::{Tensor, shape_ty};
+    ///
+    /// let a = Tensor::<4, 2, shape_ty!(2, 2)>::fill(0.3);
+    /// let b = Tensor::fill(0.1 + 0.2);
+    /// assert!(a.approx_eq(&b, 1e-12));
+    /// assert!(!a.approx_eq(&Tensor::fill(0.31), 1e-3));
+    /// ```
+    pub fn approx_eq(&self, other: &Self, tol: T) -> bool {
+        self.data
+            .iter()
+            .zip(other.data.iter())
+            .all(|(&a, &b)| (a - b).abs() <= tol)
+    }
+
+    /// Sum of the element-wise products, treating both tensors as flat vectors
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::{Tensor, shape_ty};
+    ///
+    /// let ones = Tensor::<6, 2, shape_ty!(2, 3)>::ones();
+    /// assert_eq!(ones.dot(&ones), 6.);
+    /// assert_eq!(Tensor::from([3., 4.]).norm(), 5.);
+    /// ```
+    pub fn dot(&self, other: &Self) -> T {
+        self.data
+            .iter()
+            .zip(other.data.iter())
+            .map(|(&a, &b)| a * b)
+            .sum()
+    }
+
+    /// Euclidean (L2) norm over all elements
+    pub fn norm(&self) -> T {
+        self.dot(self).sqrt()
+    }
+
+    /// Applies `f` to every element, into a new tensor of the same shape
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let t = nn_utils::tensor![[1., -2.], [3., -4.]];
+    /// assert_eq!(t.map(|x| x * 2.0).as_slice(), &[2., -4., 6., -8.]);
+    ///
+    /// let relu = t.map(|x| x.max(0.0));
+    /// assert_eq!(t.zip_map(&relu, |x, r| x - r).as_slice(), &[0., -2., 0., -4.]);
+    /// ```
+    pub fn map(&self, f: impl Fn(T) -> T) -> Self {
+        Tensor {
+            data: Box::new(array::from_fn(|i| f(self.data[i]))),
+            _shape_marker: PhantomData,
+        }
+    }
+
+    /// Applies `f` to each pair of corresponding elements, into a new tensor of the same shape
+    pub fn zip_map(&self, other: &Self, f: impl Fn(T, T) -> T) -> Self {
+        Tensor {
+            data: Box::new(array::from_fn(|i| f(self.data[i], other.data[i]))),
+            _shape_marker: PhantomData,
+        }
+    }
+
+    /// Adds `s` to every element
+    pub fn add_scalar(&self, s: T) -> Self {
+        self.map(|x| x + s)
+    }
+
+    /// Adds `values[i]` to every element whose index along `axis` is `i`, like a per-channel bias
+    /// on a `(C, H, W)` tensor along axis 0
+    ///
+    /// Panics if `axis >= D` or `values` doesn't have one entry per index along `axis`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let t = nn_utils::tensor![[1., 2., 3.], [4., 5., 6.]];
+    ///
+    /// let rows = t.add_broadcast(&[10., 20.], 0);
+    /// assert_eq!(rows.as_slice(), &[11., 12., 13., 24., 25., 26.]);
+    ///
+    /// let columns = t.add_broadcast(&[0.5, 0., -1.], 1);
+    /// assert_eq!(columns.as_slice(), &[1.5, 2., 2., 4.5, 5., 5.]);
+    ///
+    /// assert_eq!(t.add_scalar(1.).as_slice(), &[2., 3., 4., 5., 6., 7.]);
+    /// ```
+    ///
+    /// ```rust,should_panic
+    /// let t = nn_utils::tensor![[1., 2., 3.], [4., 5., 6.]];
+    /// t.add_broadcast(&[1., 2., 3.], 0);
+    /// ```
+    pub fn add_broadcast(&self, values: &[T], axis: usize) -> Self
+    where
+        Shape: ShapeDims<D>,
+    {
+        assert!(
+            axis < D,
+            "axis {axis} is out of bounds for a rank {D} tensor"
+        );
+        let len = Shape::DIMS[axis];
+        assert_eq!(
+            values.len(),
+            len,
+            "axis {axis} has {len} indices, but {} values were given",
+            values.len()
+        );
+
+        // elements sharing an index along `axis` come in contiguous runs of `inner`
+        let inner: usize = Shape::DIMS[axis + 1..].iter().product();
+        Tensor {
+            data: Box::new(array::from_fn(|i| self.data[i] + values[i / inner % len])),
+            _shape_marker: PhantomData,
+        }
+    }
+
+    /// The `(min, max)` of each slice along `channel_axis`, e.g. to pick per-channel quantization
+    /// ranges
+    ///
+    /// Panics if `channel_axis >= D` or the tensor is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let t = nn_utils::tensor![[[1., -2.], [3., 0.5]], [[-4., 8.], [2., 6.]]];
+    ///
+    /// assert_eq!(t.channel_min_max(0), vec![(-2., 3.), (-4., 8.)]);
+    /// assert_eq!(t.channel_min_max(1), vec![(-4., 8.), (0.5, 6.)]);
+    /// assert_eq!(t.channel_min_max(2), vec![(-4., 3.), (-2., 8.)]);
+    /// ```
+    pub fn channel_min_max(&self, channel_axis: usize) -> Vec<(T, T)>
+    where
+        Shape: ShapeDims<D>,
+    {
+        assert!(
+            channel_axis < D,
+            "axis {channel_axis} is out of bounds for a rank {D} tensor"
+        );
+        let len = Shape::DIMS[channel_axis];
+        let inner: usize = Shape::DIMS[channel_axis + 1..].iter().product();
+
+        // each channel starts out at its first element, which sits at `c * inner`
+        let mut extremes: Vec<(T, T)> = (0..len)
+            .map(|c| (self.data[c * inner], self.data[c * inner]))
+            .collect();
+        for (i, &x) in self.data.iter().enumerate() {
+            let (min, max) = &mut extremes[i / inner % len];
+            if x < *min {
+                *min = x;
+            }
+            if x > *max {
+                *max = x;
+            }
+        }
+        extremes
+    }
+
+    /// Flat index of the largest element, e.g. the predicted class of a softmax output
+    ///
+    /// Ties go to the first occurrence. Panics if the tensor is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let t = nn_utils::tensor![[0.1, 0.7], [0.05, 0.15]];
+    /// assert_eq!(t.argmax(), 1);
+    ///
+    /// let tied = nn_utils::tensor![[0.4, 0.1], [0.4, 0.1]];
+    /// assert_eq!(tied.argmax(), 0);
+    /// ```
+    pub fn argmax(&self) -> usize {
+        assert!(N > 0, "argmax of an empty tensor");
+        let mut best = 0;
+        for (i, &x) in self.data.iter().enumerate() {
+            if x > self.data[best] {
+                best = i;
+            }
+        }
+        best
+    }
+
+    /// The index along `axis` of the largest element of each slice through it, in row-major order
+    /// of the remaining axes
+    ///
+    /// Ties go to the first occurrence. Panics if `axis >= D` or `axis` has no indices.
+    ///
+    /// # Examples
+    ///
+    /// A batch of two samples by three classes:
+    /// ```rust
+    /// let t = nn_utils::tensor![[0.2, 0.5, 0.3], [0.6, 0.1, 0.3]];
+    ///
+    /// assert_eq!(t.argmax_axis(1), vec![1, 0]);
+    /// assert_eq!(t.argmax_axis(0), vec![1, 0, 0]);
+    /// ```
+    pub fn argmax_axis(&self, axis: usize) -> Vec<usize>
+    where
+        Shape: ShapeDims<D>,
+    {
+        assert!(
+            axis < D,
+            "axis {axis} is out of bounds for a rank {D} tensor"
+        );
+        let len = Shape::DIMS[axis];
+        assert!(len > 0, "argmax along axis {axis}, which has no indices");
+
+        // a slice through `axis` visits `len` elements `inner` apart
+        let inner: usize = Shape::DIMS[axis + 1..].iter().product();
+        let outer = N / (len * inner).max(1);
+        (0..outer * inner)
+            .map(|s| {
+                let start = s / inner * len * inner + s % inner;
+                let at = |k: usize| self.data[start + k * inner];
+                (1..len).fold(0, |best, k| if at(k) > at(best) { k } else { best })
+            })
+            .collect()
     }
 }
 
-pub trait GetFromIndexMut<const N: usize> {
-    fn at_mut(&mut self, index: [usize; N]) -> &mut f64;
+/// Error from [`Tensor::try_reshape`] when the target shape holds a different number of elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReshapeError {
+    /// Elements in the tensor
+    pub expected: usize,
+    /// Elements described by the target shape
+    pub found: usize,
 }
 
-impl GetFromIndexMut<0> for f64 {
-    fn at_mut(&mut self, _index: [usize; 0]) -> &mut f64 {
-        self
+impl fmt::Display for ReshapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cannot reshape a tensor of {} elements into a shape of {} elements",
+            self.expected, self.found
+        )
     }
 }
 
-// Recursive case: nested arrays
-impl<T, const M: usize, const N: usize> GetFromIndexMut<N> for [T; M]
+impl Error for ReshapeError {}
+
+/// A row-major tensor whose dims are only known at runtime
+///
+/// Returned by operations like [`Tensor::permute`] whose output shape cannot be written as a type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynTensor<const D: usize, T = f64> {
+    data: Vec<T>,
+    dims: [usize; D],
+}
+
+impl<const D: usize, T: Copy> DynTensor<D, T> {
+    pub fn dims(&self) -> [usize; D] {
+        self.dims
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    pub fn at(&self, index: [usize; D]) -> T {
+        self.data[row_major_offset(self.dims, index)]
+    }
+}
+
+impl<const D: usize, T: Copy> DynTensor<D, T> {
+    /// Joins tensors along `axis`; every other dim must agree
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use nn_utils::{ConcatError, Tensor, shape_ty};
+    ///
+    /// let t = Tensor::<6, 2, shape_ty!(2, 3)>::ones();
+    /// let (wide, tall) = (t.permute([0, 1]), t.permute([1, 0]));
+    ///
+    /// let err = nn_utils::DynTensor::concat(&[&wide, &tall], 0).unwrap_err();
+    /// assert!(matches!(err, ConcatError::ShapeMismatch { index: 1, .. }));
+    /// ```
+    pub fn concat(tensors: &[&Self], axis: usize) -> Result<DynTensor<D, T>, ConcatError> {
+        concat_parts(tensors.iter().map(|t| (&t.data[..], t.dims)), axis)
+    }
+}
+
+/// Error from [`Tensor::concat`] and [`DynTensor::concat`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConcatError {
+    /// No tensors were given
+    Empty,
+    AxisOutOfBounds {
+        axis: usize,
+        rank: usize,
+    },
+    /// The tensor at `index` differs from the first one on an axis other than the concatenation axis
+    ShapeMismatch {
+        index: usize,
+        expected: Vec<usize>,
+        found: Vec<usize>,
+    },
+}
+
+impl fmt::Display for ConcatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConcatError::Empty => write!(f, "cannot concatenate zero tensors"),
+            ConcatError::AxisOutOfBounds { axis, rank } => {
+                write!(f, "axis {axis} is out of bounds for tensors of rank {rank}")
+            }
+            ConcatError::ShapeMismatch {
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "tensor {index} has shape {found:?}, incompatible with {expected:?}"
+            ),
+        }
+    }
+}
+
+impl Error for ConcatError {}
+
+fn concat_parts<'a, const D: usize, T: Copy + 'a>(
+    parts: impl Iterator<Item = (&'a [T], [usize; D])> + Clone,
+    axis: usize,
+) -> Result<DynTensor<D, T>, ConcatError> {
+    if axis >= D {
+        return Err(ConcatError::AxisOutOfBounds { axis, rank: D });
+    }
+
+    let (_, first) = parts.clone().next().ok_or(ConcatError::Empty)?;
+    let mut dims = first;
+    dims[axis] = 0;
+
+    for (index, (_, part)) in parts.clone().enumerate() {
+        let compatible = (0..D).all(|i| i == axis || part[i] == first[i]);
+        if !compatible {
+            return Err(ConcatError::ShapeMismatch {
+                index,
+                expected: first.to_vec(),
+                found: part.to_vec(),
+            });
+        }
+        dims[axis] += part[axis];
+    }
+
+    // every input contributes one contiguous run of `dims[axis..]` elements per outer index
+    let outer: usize = dims[..axis].iter().product();
+    let mut data = Vec::with_capacity(dims.iter().product());
+    for o in 0..outer {
+        for (part, part_dims) in parts.clone() {
+            let run: usize = part_dims[axis..].iter().product();
+            data.extend_from_slice(&part[o * run..][..run]);
+        }
+    }
+
+    Ok(DynTensor { data, dims })
+}
+
+fn row_major_strides<const D: usize>(dims: [usize; D]) -> [usize; D] {
+    let mut strides = [1; D];
+    for i in (0..D.saturating_sub(1)).rev() {
+        strides[i] = strides[i + 1] * dims[i + 1];
+    }
+    strides
+}
+
+// offset of `index` into a row-major buffer, panicking if any coordinate is out of bounds
+fn row_major_offset<const D: usize>(dims: [usize; D], index: [usize; D]) -> usize {
+    let strides = row_major_strides(dims);
+    let mut flat = 0;
+    for axis in 0..D {
+        assert!(
+            index[axis] < dims[axis],
+            "index {} out of bounds for axis {axis} of length {}",
+            index[axis],
+            dims[axis]
+        );
+        flat += index[axis] * strides[axis];
+    }
+    flat
+}
+
+/// A borrowed, zero-copy window onto a (sub-)tensor
+///
+/// Elements are located from row-major `strides` rather than by reinterpreting the buffer as `Shape`.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut t = nn_utils::tensor!(2, 3, 4);
+/// t.set([1, 2, 3], 7.);
+///
+/// let view = t.view();
+/// assert_eq!(view[[1, 2, 3]], 7.);
+///
+/// // each `axis` call drops the leading dimension without copying
+/// let row = view.axis(1).axis(2);
+/// assert_eq!(row[[3]], 7.);
+/// assert_eq!(row.strides(), [1]);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct TensorView<'a, const N: usize, const D: usize, Shape, T = f64> {
+    data: &'a [T],
+    strides: [usize; D],
+    _shape_marker: PhantomData<Shape>,
+}
+
+impl<'a, const N: usize, const D: usize, Shape, T> TensorView<'a, N, D, Shape, T>
 where
-    T: GetFromIndexMut<{ N - 1 }>,
+    Shape: ShapeDims<D>,
 {
-    default fn at_mut(&mut self, index: [usize; N]) -> &mut f64 {
-        self[index[0]].at_mut(core::array::from_fn(|i| index[i + 1]))
+    pub fn strides(&self) -> [usize; D] {
+        self.strides
+    }
+
+    /// The `D - 1` dimensional sub-tensor at `index` along the leading axis
+    pub fn axis(
+        self,
+        index: usize,
+    ) -> TensorView<
+        'a,
+        { <<Shape as ops::Index<usize>>::Output as ArraySize>::SIZE },
+        { D - 1 },
+        <Shape as ops::Index<usize>>::Output,
+        T,
+    >
+    where
+        Shape: ops::Index<usize>,
+        <Shape as ops::Index<usize>>::Output: Sized + ArraySize,
+    {
+        assert!(
+            index < Shape::DIMS[0],
+            "index {index} out of bounds for axis of length {}",
+            Shape::DIMS[0]
+        );
+        let len = <<Shape as ops::Index<usize>>::Output as ArraySize>::SIZE;
+
+        TensorView {
+            data: &self.data[index * self.strides[0]..][..len],
+            strides: array::from_fn(|i| self.strides[i + 1]),
+            _shape_marker: PhantomData,
+        }
     }
 }
 
+impl<const N: usize, const D: usize, Shape, T> ops::Index<[usize; D]>
+    for TensorView<'_, N, D, Shape, T>
+where
+    Shape: ShapeDims<D>,
+{
+    type Output = T;
+
+    fn index(&self, index: [usize; D]) -> &T {
+        &self.data[Shape::flat_index(index)]
+    }
+}
+
+/// The dimensions encoded by a nested-array shape type, e.g. `[2, 3]` for `shape_ty!(2, 3)`
+///
+/// # Examples
+///
+/// ```rust
+/// use nn_utils::{ShapeDims, Tensor, shape_ty};
+///
+/// type Shape = shape_ty!(2, 3, 4);
+/// assert_eq!(<Shape as ShapeDims<3>>::DIMS, [2, 3, 4]);
+/// assert_eq!(<Shape as ShapeDims<3>>::strides(), [12, 4, 1]);
+///
+/// // offsets agree with the manual `ic * IH * IW + y * IW + x`
+/// let t: Tensor<24, 3, Shape> = Tensor::from(std::array::from_fn(|i| i as f64)).reshape();
+/// for ic in 0..2 {
+///     for y in 0..3 {
+///         for x in 0..4 {
+///             assert_eq!(*t.at([ic, y, x]), (ic * 3 * 4 + y * 4 + x) as f64);
+///         }
+///     }
+/// }
+/// ```
+pub trait ShapeDims<const D: usize> {
+    const DIMS: [usize; D];
+
+    /// Row-major strides: how many elements to skip per step along each axis
+    fn strides() -> [usize; D] {
+        row_major_strides(Self::DIMS)
+    }
+
+    /// Offset of `index` into a row-major buffer, panicking if any coordinate is out of bounds
+    fn flat_index(index: [usize; D]) -> usize {
+        row_major_offset(Self::DIMS, index)
+    }
+}
+
+impl ShapeDims<0> for f64 {
+    const DIMS: [usize; 0] = [];
+}
+
+// Recursive case: nested arrays prepend their length to the element's dims
+impl<T, const M: usize, const D: usize> ShapeDims<D> for [T; M]
+where
+    T: ShapeDims<{ D - 1 }>,
+{
+    const DIMS: [usize; D] = {
+        let mut dims = [M; D];
+        let mut i = 1;
+        while i < D {
+            dims[i] = T::DIMS[i - 1];
+            i += 1;
+        }
+        dims
+    };
+}
+
 pub trait ArraySize {
     const SIZE: usize;
+
+    /// Appends the `SIZE` elements of a nested array value in row-major order
+    fn flatten_into(&self, out: &mut Vec<f64>);
 }
 
 // Base case: f64 has "size" 1
 impl ArraySize for f64 {
     const SIZE: usize = 1;
+
+    fn flatten_into(&self, out: &mut Vec<f64>) {
+        out.push(*self);
+    }
 }
 
 // Recursive case: [T; N] has size N * T::SIZE
 impl<T: ArraySize, const N: usize> ArraySize for [T; N] {
     const SIZE: usize = N * T::SIZE;
+
+    fn flatten_into(&self, out: &mut Vec<f64>) {
+        for item in self {
+            item.flatten_into(out);
+        }
+    }
+}
+
+/// Prints nested rows following the decoded dims, then the shape
+///
+/// Honors a precision, e.g. `{:.2}`.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut t = nn_utils::tensor!(2, 3);
+/// t.set([1, 0], 1.5);
+/// assert_eq!(t.to_string(), "[[0, 0, 0],\n [1.5, 0, 0]]\nshape: [2, 3]");
+/// ```
+impl<const N: usize, const D: usize, Shape, T: Scalar> fmt::Display for Tensor<N, D, Shape, T>
+where
+    Shape: ShapeDims<D>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_nested(f, &self.data[..], &Shape::DIMS, 0)?;
+        write!(f, "\nshape: {:?}", Shape::DIMS)
+    }
+}
+
+impl<const D: usize, T: Scalar> fmt::Display for DynTensor<D, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_nested(f, &self.data, &self.dims, 0)?;
+        write!(f, "\nshape: {:?}", self.dims)
+    }
 }
 
-impl<const N: usize, const D: usize, Shape> Default for Tensor<N, D, Shape>
+// NumPy-style nesting: inner rows are separated by a newline per remaining axis and aligned under
+// their opening bracket
+fn write_nested<T: Scalar>(
+    f: &mut fmt::Formatter<'_>,
+    data: &[T],
+    dims: &[usize],
+    depth: usize,
+) -> fmt::Result {
+    let Some((&len, rest)) = dims.split_first() else {
+        return match f.precision() {
+            Some(p) => write!(f, "{:.*}", p, data[0]),
+            None => write!(f, "{}", data[0]),
+        };
+    };
+
+    let chunk = rest.iter().product::<usize>();
+    let separator = if rest.is_empty() {
+        ", ".to_string()
+    } else {
+        format!(",{}{}", "\n".repeat(rest.len()), " ".repeat(depth + 1))
+    };
+
+    write!(f, "[")?;
+    for i in 0..len {
+        if i > 0 {
+            write!(f, "{separator}")?;
+        }
+        write_nested(f, &data[i * chunk..][..chunk], rest, depth + 1)?;
+    }
+    write!(f, "]")
+}
+
+impl<const N: usize, const D: usize, Shape, T: Scalar> Default for Tensor<N, D, Shape, T>
 where
     Shape: ops::Index<usize>,
     <Shape as ops::Index<usize>>::Output: Sized + ArraySize,
@@ -155,10 +1125,32 @@ where
     }
 }
 
+/// Exact element-wise equality; two tensors of the same type always share a shape. Use
+/// [`Tensor::approx_eq`] to allow for rounding
+///
+/// # Examples
+///
+/// ```rust
+/// use nn_utils::{Tensor, shape_ty};
+///
+/// let a = Tensor::<4, 2, shape_ty!(2, 2)>::fill(0.3);
+/// assert_eq!(a, a.clone());
+/// assert_eq!(a, a.add_scalar(0.0));
+/// assert_ne!(a, Tensor::fill(0.1 + 0.2));
+/// assert_ne!(a, a.map(f64::sqrt));
+/// ```
+impl<const N: usize, const D: usize, Shape, T: PartialEq> PartialEq for Tensor<N, D, Shape, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+}
+
 // currently disallow adding two `&Tensor` because I need to overwrite one of them and also be able to own the `data` as I am dealing with `Box<[_; _]>`
-impl<const N: usize, const D: usize, Shape> ops::Add<&Tensor<N, D, Shape>> for Tensor<N, D, Shape> {
-    type Output = Tensor<N, D, Shape>;
-    fn add(mut self, rhs: &Tensor<N, D, Shape>) -> Self::Output {
+impl<const N: usize, const D: usize, Shape, T: Scalar> ops::Add<&Tensor<N, D, Shape, T>>
+    for Tensor<N, D, Shape, T>
+{
+    type Output = Tensor<N, D, Shape, T>;
+    fn add(mut self, rhs: &Tensor<N, D, Shape, T>) -> Self::Output {
         for (i, v) in self.data.iter_mut().enumerate() {
             *v += rhs.data[i];
         }
@@ -170,9 +1162,9 @@ impl<const N: usize, const D: usize, Shape> ops::Add<&Tensor<N, D, Shape>> for T
     }
 }
 
-impl<const N: usize, const D: usize, Shape> ops::Div<f64> for Tensor<N, D, Shape> {
-    type Output = Tensor<N, D, Shape>;
-    fn div(mut self, rhs: f64) -> Self::Output {
+impl<const N: usize, const D: usize, Shape, T: Scalar> ops::Div<T> for Tensor<N, D, Shape, T> {
+    type Output = Tensor<N, D, Shape, T>;
+    fn div(mut self, rhs: T) -> Self::Output {
         for v in self.data.iter_mut() {
             *v /= rhs;
         }
@@ -184,6 +1176,36 @@ impl<const N: usize, const D: usize, Shape> ops::Div<f64> for Tensor<N, D, Shape
     }
 }
 
+/// Asserts that two tensors of the same type are equal to within a tolerance
+///
+/// # Examples
+///
+/// ```rust
+/// use nn_utils::{Tensor, assert_tensor_approx, shape_ty};
+///
+/// let t = Tensor::<3, 1, shape_ty!(3)>::fill(1. / 3.);
+/// assert_tensor_approx!(t.clone() + &t, Tensor::fill(2. / 3.), 1e-12);
+/// ```
+///
+/// ```rust,should_panic
+/// use nn_utils::{Tensor, assert_tensor_approx, shape_ty};
+///
+/// assert_tensor_approx!(Tensor::<3, 1, shape_ty!(3)>::ones(), Tensor::zeros(), 0.5);
+/// ```
+#[macro_export]
+macro_rules! assert_tensor_approx {
+    ($left:expr, $right:expr, $tol:expr $(,)?) => {{
+        let (left, right, tol) = (&$left, &$right, $tol);
+        assert!(
+            left.approx_eq(right, tol),
+            "tensors differ by more than {}\n  left: {:?}\n right: {:?}",
+            tol,
+            left,
+            right,
+        );
+    }};
+}
+
 #[macro_export]
 macro_rules! shape_ty {
     ($d:expr) => {
@@ -202,8 +1224,61 @@ macro_rules! __dim_ty {
     ($head:tt $($tail:tt)*) => { 1 + $crate::__dim_ty!($($tail)*) };
 }
 
+// shape type of a nested array literal, e.g. `[[f64; 2]; 3]` for three rows of two
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __nested_shape {
+    ([ [$($inner:tt)*] $(, $rest:tt)* $(,)? ]) => {
+        [$crate::__nested_shape!([$($inner)*]); $crate::__dim_ty!([$($inner)*] $($rest)*)]
+    };
+    ([ $($x:expr),+ $(,)? ]) => {
+        [f64; $crate::__dim_ty!($($x)*)]
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __nested_depth {
+    ([ [$($inner:tt)*] $(, $rest:tt)* $(,)? ]) => {
+        1 + $crate::__nested_depth!([$($inner)*])
+    };
+    ([ $($x:expr),+ $(,)? ]) => {
+        1
+    };
+}
+
+/// A zero tensor of the given dims, or a tensor holding a nested array literal
+///
+/// The literal form needs at least two levels of brackets; use [`Tensor::from`] for a flat array.
+///
+/// # Examples
+///
+/// ```rust
+/// let zeros = nn_utils::tensor!(2, 3);
+/// assert_eq!(*zeros.at([1, 2]), 0.);
+///
+/// let t = nn_utils::tensor![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]];
+/// assert_eq!(*t.at([0, 1]), 2.0);
+/// assert_eq!(*t.at([1, 2]), 6.0);
+///
+/// let cube = nn_utils::tensor![[[1.0, -2.0]], [[3.0, 4.0]]];
+/// assert_eq!(*cube.at([0, 0, 1]), -2.0);
+/// assert!(cube.to_string().ends_with("shape: [2, 1, 2]"));
+/// ```
 #[macro_export]
 macro_rules! tensor {
+    ($([$($row:tt)*]),+ $(,)?) => {
+        {
+            type Shape = $crate::__nested_shape!([$([$($row)*]),+]);
+
+            const N: usize = <Shape as $crate::ArraySize>::SIZE;
+
+            const D: usize = $crate::__nested_depth!([$([$($row)*]),+]);
+
+            <$crate::Tensor::<N, D, Shape>>::from_nested([$([$($row)*]),+])
+        }
+    };
+
     ($first:expr $(, $rest:expr)* $(,)?) => {
         {
             // number of elements
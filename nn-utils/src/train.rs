@@ -0,0 +1,253 @@
+use crate::optim::Adam;
+use std::ops;
+
+/// A network that [`train_epoch`] can fit, one sample at a time
+pub trait NetworkTrait<const IN: usize, const OUT: usize> {
+    fn forward(&self, input: &[f32; IN]) -> [f32; OUT];
+
+    /// Accumulates the parameter gradients for one sample, given the loss gradient w.r.t. the
+    /// output of [`NetworkTrait::forward`] on `input`
+    fn backward(&mut self, input: &[f32; IN], grad_output: &[f32; OUT]);
+
+    /// Calls `f` on every parameter group and its accumulated gradient, always in the same order
+    fn for_each_param(&mut self, f: &mut dyn FnMut(&mut [f32], &[f32]));
+
+    fn zero_grads(&mut self);
+
+    /// The parameter gradients for one sample, without updating anything
+    ///
+    /// Clears the accumulated gradients first. Add the results up across samples, then step with
+    /// [`NetworkTrait::apply_gradients`].
+    ///
+    /// # Examples
+    ///
+    /// Gradients taken at the same parameters can be applied together or one by one:
+    /// ```rust
+    /// use nn_utils::network::DenseLayer;
+    /// use nn_utils::train::{Mse, NetworkTrait};
+    ///
+    /// let init = || DenseLayer::<2, 1>::from_parts(Box::new([[0.5, -0.25]]), Box::new([0.1]));
+    /// let samples = [([1.0, 2.0], [1.0]), ([-1.0, 0.5], [0.0]), ([3.0, -2.0], [2.0])];
+    ///
+    /// let mut net = init();
+    /// let grads: Vec<_> = samples.iter().map(|(x, y)| net.compute_gradients(x, y, &Mse)).collect();
+    ///
+    /// let mut total = grads[0].clone();
+    /// for g in &grads[1..] {
+    ///     total += g;
+    /// }
+    /// let mut batched = init();
+    /// batched.apply_gradients(&total, 0.1);
+    ///
+    /// let mut stepped = init();
+    /// for g in &grads {
+    ///     stepped.apply_gradients(g, 0.1);
+    /// }
+    ///
+    /// for (a, b) in batched.weights()[0].iter().chain(batched.biases())
+    ///     .zip(stepped.weights()[0].iter().chain(stepped.biases()))
+    /// {
+    ///     assert!((a - b).abs() < 1e-6);
+    /// }
+    /// assert_ne!(batched.weights(), init().weights());
+    /// ```
+    fn compute_gradients(
+        &mut self,
+        input: &[f32; IN],
+        target: &[f32; OUT],
+        loss_fn: &impl Loss,
+    ) -> Gradients
+    where
+        Self: Sized,
+    {
+        self.zero_grads();
+        let mut grad = [0.0; OUT];
+        loss_fn.loss(&self.forward(input), target, &mut grad);
+        self.backward(input, &grad);
+
+        let mut groups = Vec::new();
+        self.for_each_param(&mut |_, g| groups.push(g.to_vec()));
+        Gradients { groups }
+    }
+
+    /// Plain gradient descent: subtracts `eta` times `grads` from the parameters
+    ///
+    /// Panics if `grads` came from a network with differently sized parameters.
+    fn apply_gradients(&mut self, grads: &Gradients, eta: f32) {
+        let mut groups = grads.groups.iter();
+        self.for_each_param(&mut |params, _| {
+            let g = groups
+                .next()
+                .expect("gradients are missing a parameter group");
+            assert_eq!(params.len(), g.len(), "gradient group has the wrong size");
+            for (p, g) in params.iter_mut().zip(g) {
+                *p -= eta * g;
+            }
+        });
+    }
+}
+
+/// Owned parameter gradients from [`NetworkTrait::compute_gradients`], one group per parameter
+/// group of the network
+///
+/// Gradients of several samples add up with `+=`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradients {
+    groups: Vec<Vec<f32>>,
+}
+
+impl Gradients {
+    /// In the order of [`NetworkTrait::for_each_param`]
+    pub fn groups(&self) -> &[Vec<f32>] {
+        &self.groups
+    }
+}
+
+impl ops::AddAssign<&Gradients> for Gradients {
+    fn add_assign(&mut self, rhs: &Gradients) {
+        assert_eq!(
+            self.groups.len(),
+            rhs.groups.len(),
+            "gradients of different networks"
+        );
+        for (a, b) in self.groups.iter_mut().zip(&rhs.groups) {
+            assert_eq!(a.len(), b.len(), "gradients of different networks");
+            for (a, b) in a.iter_mut().zip(b) {
+                *a += b;
+            }
+        }
+    }
+}
+
+/// A per-sample loss
+pub trait Loss {
+    /// Loss of `output` against `target`, writing its gradient w.r.t. `output` into `grad`
+    fn loss(&self, output: &[f32], target: &[f32], grad: &mut [f32]) -> f32;
+}
+
+/// Mean squared error over the outputs of a sample
+///
+/// # Examples
+///
+/// ```rust
+/// use nn_utils::train::{Loss, Mse};
+///
+/// let mut grad = [0.0; 2];
+/// assert_eq!(Mse.loss(&[1.0, 4.0], &[2.0, 2.0], &mut grad), 2.5);
+/// assert_eq!(grad, [-1.0, 2.0]);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Mse;
+
+impl Loss for Mse {
+    fn loss(&self, output: &[f32], target: &[f32], grad: &mut [f32]) -> f32 {
+        let n = output.len() as f32;
+        let mut sum = 0.0;
+        for ((o, t), g) in output.iter().zip(target).zip(grad) {
+            let diff = o - t;
+            sum += diff * diff;
+            *g = 2.0 * diff / n;
+        }
+        sum / n
+    }
+}
+
+/// An [`Adam`] along with the moment buffers for every parameter of one network
+///
+/// The buffers are sized on the first step, and the parameters are stepped as one flat group, so
+/// `adam.t` counts whole updates.
+#[derive(Debug, Clone)]
+pub struct AdamState {
+    pub adam: Adam,
+    m: Vec<f32>,
+    v: Vec<f32>,
+}
+
+impl AdamState {
+    pub fn new(adam: Adam) -> Self {
+        Self {
+            adam,
+            m: Vec::new(),
+            v: Vec::new(),
+        }
+    }
+
+    /// Applies one update to `net` from its accumulated gradients
+    pub fn step<const IN: usize, const OUT: usize>(
+        &mut self,
+        net: &mut impl NetworkTrait<IN, OUT>,
+    ) {
+        let (mut params, mut grads) = (Vec::new(), Vec::new());
+        net.for_each_param(&mut |p, g| {
+            params.extend_from_slice(p);
+            grads.extend_from_slice(g);
+        });
+
+        self.m.resize(params.len(), 0.0);
+        self.v.resize(params.len(), 0.0);
+        self.adam
+            .step(&mut params, &grads, &mut self.m, &mut self.v);
+
+        // scatter back in the same order they were gathered
+        let mut rest = &params[..];
+        net.for_each_param(&mut |p, _| {
+            let (head, tail) = rest.split_at(p.len());
+            p.copy_from_slice(head);
+            rest = tail;
+        });
+    }
+}
+
+/// One pass over `data`, updating `net` after every sample, returning the mean loss
+///
+/// Each loss is measured before its sample's update. The mean is NaN if `data` is empty.
+///
+/// # Examples
+///
+/// Fitting `y = 2x + 1` with a single neuron:
+/// ```rust
+/// use nn_utils::network::DenseLayer;
+/// use nn_utils::optim::Adam;
+/// use nn_utils::train::{AdamState, Mse, train_epoch};
+///
+/// let data: Vec<[f32; 1]> = (0..8).map(|i| [i as f32 / 4.0]).collect();
+/// let targets: Vec<[f32; 1]> = data.iter().map(|[x]| [2.0 * x + 1.0]).collect();
+///
+/// let mut net = DenseLayer::<1, 1>::init();
+/// let mut opt = AdamState::new(Adam::new(0.05));
+///
+/// let losses: Vec<f32> = (0..200)
+///     .map(|_| train_epoch(&mut net, &data, &targets, &Mse, &mut opt))
+///     .collect();
+///
+/// assert!(losses[10] < losses[0]);
+/// assert!(losses[199] < losses[10]);
+/// assert!(losses[199] < 1e-3);
+/// ```
+pub fn train_epoch<const IN: usize, const OUT: usize, N: NetworkTrait<IN, OUT>>(
+    net: &mut N,
+    data: &[[f32; IN]],
+    targets: &[[f32; OUT]],
+    loss_fn: &impl Loss,
+    opt: &mut AdamState,
+) -> f32 {
+    assert_eq!(
+        data.len(),
+        targets.len(),
+        "{} samples but {} targets",
+        data.len(),
+        targets.len()
+    );
+
+    let mut total = 0.0;
+    let mut grad = [0.0; OUT];
+    for (input, target) in data.iter().zip(targets) {
+        net.zero_grads();
+        let output = net.forward(input);
+        total += loss_fn.loss(&output, target, &mut grad);
+        net.backward(input, &grad);
+        opt.step(net);
+    }
+
+    total / data.len() as f32
+}
@@ -0,0 +1,23 @@
+#![cfg(feature = "serde")]
+
+use nn_utils::autodiff::MultiGraph;
+use nn_utils::graph;
+
+// The multi-input graph from `examples/multi_input.rs`, through JSON and back
+#[test]
+fn multi_input_graph_round_trips() {
+    let mut graph = graph! {
+        inputs: [x, z]
+        x -> Pow(2) -> @x_sq
+        z -> Cos -> @z_cos
+        (@x_sq, @z_cos) -> Add -> @result
+        output @result
+    };
+
+    let json = serde_json::to_string(&graph).unwrap();
+    let mut restored: MultiGraph = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(restored.input_names(), ["x", "z"]);
+    assert_eq!(restored.compute(&[2.0, 1.0]), graph.compute(&[2.0, 1.0]));
+    assert_eq!(restored.value_of("x_sq"), Some((4.0, 4.0)));
+}
@@ -0,0 +1,22 @@
+use nn::network as nt;
+use nn::network::NonFiniteError;
+
+// Tracks down where a NaN or infinity first appears in a forward pass
+fn main() {
+    let mut net = nt! {
+        input(2) -> dense(3) -> relu -> dense(2) -> softmax -> output
+    };
+
+    let input = [1.0, 2.0];
+    println!("checked output: {:?}", net.forward_checked(&input));
+
+    // a huge weight overflows the first dense layer's output to infinity
+    net.layers.0.weights_mut()[1] = [f32::MAX, f32::MAX];
+    // `tests/check_finite.rs` checks the reported layer and index
+    let err: NonFiniteError = net.forward_checked(&input).unwrap_err();
+    println!("{err}");
+
+    // unchecked, it only shows up at the end, as NaNs out of the softmax
+    let output = net.forward(&input);
+    println!("unchecked output: {output:?}");
+}
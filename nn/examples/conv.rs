@@ -49,7 +49,7 @@ fn main() {
         avg_out_space = avg_out_space + &cur_out_space;
     }
 
-    dbg!(avg_out_space / n as f64);
+    dbg!(avg_out_space / n as f32);
     // fun fact: appears to converge to [[4.], [4.]]
 }
 
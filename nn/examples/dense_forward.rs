@@ -0,0 +1,50 @@
+use nn::network::DenseLayer;
+use std::{hint::black_box, time::Instant};
+
+const N: usize = 1024;
+
+// The index-based loop `DenseLayer::forward` used to be, for comparison
+fn indexed_forward(weights: &[[f32; N]; N], biases: &[f32; N], input: &[f32], output: &mut [f32]) {
+    for (o, out) in output[..N].iter_mut().enumerate() {
+        let mut sum = biases[o];
+        for (w, x) in weights[o].iter().zip(&input[..N]) {
+            sum += w * x;
+        }
+        *out = sum;
+    }
+}
+
+fn main() {
+    // built on the heap, a 1024x1024 array is too big for the stack
+    let weights: Box<[[f32; N]; N]> = (0..N)
+        .map(|o| std::array::from_fn(|i| ((o * N + i) as f32).sin()))
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap();
+    let biases: Box<[f32; N]> = vec![0.5; N].try_into().unwrap();
+    let layer = DenseLayer::from_parts(weights, biases);
+
+    let input: Vec<f32> = (0..N).map(|i| (i as f32 * 0.01).cos()).collect();
+    let (mut old, mut new) = (vec![0.0; N], vec![0.0; N]);
+    let iterations = 200;
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        indexed_forward(layer.weights(), layer.biases(), black_box(&input), &mut old);
+        black_box(&old);
+    }
+    let indexed = start.elapsed();
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        layer.forward(black_box(&input), &mut new);
+        black_box(&new);
+    }
+    let iterators = start.elapsed();
+
+    // same summation order, so bit-for-bit the same
+    assert_eq!(old, new);
+
+    println!("indexed:   {indexed:?} for {iterations} passes");
+    println!("iterators: {iterators:?} for {iterations} passes");
+}
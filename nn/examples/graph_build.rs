@@ -0,0 +1,67 @@
+use nn::autodiff::{MultiGraph, NodeId, Op};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+// Counts every allocation, to compare graph building with and without temporary `Vec`s
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const NODES: usize = 10_000;
+
+// A chain alternating unary and binary ops, `add_op` deciding how the inputs are passed
+fn build(add_op: impl Fn(&mut MultiGraph, Op, NodeId, NodeId) -> NodeId) -> MultiGraph {
+    let mut graph = MultiGraph::new();
+    let x = graph.input("x".to_string());
+    let mut prev = x;
+    for i in 0..NODES {
+        prev = if i % 2 == 0 {
+            add_op(&mut graph, Op::Sin, prev, prev)
+        } else {
+            add_op(&mut graph, Op::Add, prev, x)
+        };
+    }
+    graph.output(prev);
+    graph
+}
+
+fn measure(name: &str, add_op: impl Fn(&mut MultiGraph, Op, NodeId, NodeId) -> NodeId) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let graph = black_box(build(add_op));
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+    println!("{name:<6} {NODES} ops: {allocations} allocations in {elapsed:?}");
+    drop(graph);
+}
+
+fn main() {
+    measure("vec", |graph, op, a, b| match op {
+        Op::Add => graph.operation(op, vec![a, b]),
+        _ => graph.operation(op, vec![a]),
+    });
+
+    measure("slice", |graph, op, a, b| match op {
+        Op::Add => graph.operation_from_slice(op, &[a, b]),
+        _ => graph.operation_from_slice(op, &[a]),
+    });
+}
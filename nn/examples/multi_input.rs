@@ -27,7 +27,18 @@ fn main() {
 
     println!("{}", type_name_of_val(&multi_graph));
 
-    let results = multi_graph.compute(&[2.0, 1.0]);
+    println!(
+        "Multi input - {} inputs {:?}, {} output(s)",
+        multi_graph.num_inputs(),
+        multi_graph.input_names(),
+        multi_graph.num_outputs()
+    );
+
+    // one value per input, in declaration order: x = 2.0, z = 1.0
+    let inputs: Vec<f64> = (0..multi_graph.num_inputs())
+        .map(|i| 2.0 - i as f64)
+        .collect();
+    let results = multi_graph.compute(&inputs);
     if let Some((result, derivative)) = results.first() {
         println!(
             "Multi input - f(2.0, 1.0) = {:.6}, f'(2.0, 1.0) = {:.6}",
@@ -0,0 +1,20 @@
+use nn::network as nt;
+
+// Naming a network declares its struct, so it can be stored in a field of a known type
+nt! {
+    Classifier;
+    input(4) -> dense(8) -> relu -> dense(3) -> softmax -> output
+}
+
+struct Agent {
+    policy: Classifier,
+}
+
+fn main() {
+    let agent = Agent {
+        policy: Classifier::new(),
+    };
+
+    let probs = agent.policy.forward(&[0.1, 0.2, 0.3, 0.4]);
+    println!("{}: {probs:?}", std::any::type_name::<Classifier>());
+}
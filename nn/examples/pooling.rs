@@ -0,0 +1,35 @@
+use nn::network as nt;
+
+// A small image classifier: convolution, pooling, then a dense head
+fn main() {
+    let mut net = nt! {
+        input(1, 8, 8) -> conv(4, 3) -> relu -> maxpool(2, 2) -> dense(10) -> softmax -> output
+    };
+
+    // dense layers start at zero, which would make every class equally likely
+    let weights = net.layers.3.weights_mut().as_flattened_mut();
+    for (i, w) in weights.iter_mut().enumerate() {
+        *w = (i as f32).sin();
+    }
+
+    let image: [f32; 64] = std::array::from_fn(|i| ((i % 8) as f32 - (i / 8) as f32) / 8.0);
+    let probs = net.forward(&image);
+    println!("class probabilities: {probs:?}");
+
+    // the same pipeline, one layer at a time
+    let (conv, relu, pool, dense, softmax) = &net.layers;
+    let (mut a, mut b) = ([0.0; 4 * 6 * 6], [0.0; 4 * 6 * 6]);
+    conv.forward_slice(&image, &mut a);
+    relu.forward(&a, &mut b);
+
+    // 2x2 windows halve each side of the 6x6 feature maps
+    let mut pooled = [0.0; 4 * 3 * 3];
+    pool.forward(&b, &mut pooled);
+
+    let (mut logits, mut expected) = ([0.0; 10], [0.0; 10]);
+    dense.forward(&pooled, &mut logits);
+    softmax.forward(&logits, &mut expected);
+
+    assert_eq!(probs, expected);
+    assert!((probs.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+}
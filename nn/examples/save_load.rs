@@ -0,0 +1,43 @@
+use nn::network as nt;
+
+fn main() {
+    let mut trained = nt! {
+        input(4) -> dense(8) -> relu -> dense(3) -> sigmoid -> dense(2) -> output
+    };
+
+    // stand-in for training
+    for (i, w) in trained
+        .layers
+        .0
+        .weights_mut()
+        .as_flattened_mut()
+        .iter_mut()
+        .enumerate()
+    {
+        *w = i as f32 * 0.01;
+    }
+    trained.layers.2.biases_mut().fill(0.5);
+    trained.layers.4.weights_mut()[0].fill(0.25);
+
+    let mut bytes = Vec::new();
+    trained
+        .save(&mut bytes)
+        .expect("writing to a Vec cannot fail");
+
+    let mut restored = nt! {
+        input(4) -> dense(8) -> relu -> dense(3) -> sigmoid -> dense(2) -> output
+    };
+    restored.load(&mut bytes.as_slice()).expect("shapes match");
+
+    // `tests/save_load.rs` checks that the outputs are identical
+    let input = [1.0, 2.0, 3.0, 4.0];
+    println!("trained:  {:?}", trained.forward(&input));
+    println!("restored: {:?}", restored.forward(&input));
+
+    // a network with different layer shapes refuses the saved parameters
+    let mut other = nt! {
+        input(4) -> dense(8) -> relu -> dense(2) -> output
+    };
+    let err = other.load(&mut bytes.as_slice()).unwrap_err();
+    println!("{err}");
+}
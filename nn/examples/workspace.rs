@@ -0,0 +1,28 @@
+use nn::network as nt;
+use std::{hint::black_box, time::Instant};
+
+// Compares `forward`, which allocates its buffers per call, against reusing one workspace
+fn main() {
+    let net = nt! {
+        input(64) -> dense(256) -> relu -> dense(256) -> relu -> dense(10) -> softmax -> output
+    };
+
+    let input: [f32; 64] = std::array::from_fn(|i| (i as f32 * 0.1).sin());
+    let iterations = 10_000;
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(net.forward(black_box(&input)));
+    }
+    let owned = start.elapsed();
+
+    let mut workspace = net.workspace();
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(net.forward_with_workspace(black_box(&input), &mut workspace));
+    }
+    let reused = start.elapsed();
+
+    println!("forward:                {owned:?} for {iterations} passes");
+    println!("forward_with_workspace: {reused:?} for {iterations} passes");
+}
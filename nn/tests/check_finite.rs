@@ -0,0 +1,31 @@
+use nn::network as nt;
+use nn::network::NonFiniteError;
+
+#[test]
+fn finite_forward_matches_unchecked() {
+    let net = nt! {
+        input(2) -> dense(3) -> relu -> dense(2) -> softmax -> output
+    };
+    let input = [1.0, 2.0];
+    assert_eq!(net.forward_checked(&input), Ok(net.forward(&input)));
+}
+
+#[test]
+fn overflowing_weights_report_first_layer_and_index() {
+    let mut net = nt! {
+        input(2) -> dense(3) -> relu -> dense(2) -> softmax -> output
+    };
+
+    // overflows the first dense layer's second output to infinity
+    net.layers.0.weights_mut()[1] = [f32::MAX, f32::MAX];
+
+    let err = net.forward_checked(&[1.0, 2.0]).unwrap_err();
+    assert_eq!(
+        err,
+        NonFiniteError {
+            layer: 0,
+            index: 1,
+            value: f32::INFINITY,
+        }
+    );
+}
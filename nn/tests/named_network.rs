@@ -0,0 +1,51 @@
+use nn::network as nt;
+
+nt! {
+    Encoder;
+    input(8) -> dense(4) -> tanh -> dense(2) -> output
+}
+
+nt! {
+    pub Classifier;
+    input(2) -> dense(3) -> relu -> dense(3) -> softmax -> output
+}
+
+// each network is a field of a known type
+struct Model {
+    encoder: Encoder,
+    classifier: Classifier,
+}
+
+fn fill(weights: &mut [f32]) {
+    for (i, w) in weights.iter_mut().enumerate() {
+        *w = (i as f32).cos();
+    }
+}
+
+#[test]
+fn named_networks_coexist() {
+    let mut model = Model {
+        encoder: Encoder::new(),
+        classifier: Classifier::new(),
+    };
+    fill(model.encoder.layers.0.weights_mut().as_flattened_mut());
+    fill(model.encoder.layers.2.weights_mut().as_flattened_mut());
+    fill(model.classifier.layers.0.weights_mut().as_flattened_mut());
+    fill(model.classifier.layers.2.weights_mut().as_flattened_mut());
+
+    let input: [f32; 8] = std::array::from_fn(|i| i as f32 / 8.0);
+    let code: [f32; 2] = model.encoder.forward(&input);
+    assert_ne!(code, [0.0; 2]);
+
+    let probs: [f32; 3] = model.classifier.forward(&code);
+    assert!((probs.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+    assert_ne!(probs, [1.0 / 3.0; 3]);
+
+    // an anonymous network with the same layers and weights agrees with the named one
+    let mut anonymous = nt! {
+        input(2) -> dense(3) -> relu -> dense(3) -> softmax -> output
+    };
+    fill(anonymous.layers.0.weights_mut().as_flattened_mut());
+    fill(anonymous.layers.2.weights_mut().as_flattened_mut());
+    assert_eq!(anonymous.forward(&code), probs);
+}
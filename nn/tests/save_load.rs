@@ -0,0 +1,52 @@
+use nn::network as nt;
+use std::io::ErrorKind;
+
+// stand-in for training, so the round trip has something other than zeros to preserve
+fn fill(weights: &mut [f32], scale: f32) {
+    for (i, w) in weights.iter_mut().enumerate() {
+        *w = (i as f32 * scale).sin();
+    }
+}
+
+#[test]
+fn round_trip_reproduces_forward() {
+    let mut trained = nt! {
+        input(4) -> dense(8) -> relu -> dense(3) -> sigmoid -> dense(2) -> output
+    };
+    fill(trained.layers.0.weights_mut().as_flattened_mut(), 0.3);
+    fill(trained.layers.2.weights_mut().as_flattened_mut(), 0.7);
+    fill(trained.layers.4.weights_mut().as_flattened_mut(), 1.1);
+    trained.layers.2.biases_mut().fill(0.5);
+
+    let mut bytes = Vec::new();
+    trained.save(&mut bytes).unwrap();
+
+    let mut restored = nt! {
+        input(4) -> dense(8) -> relu -> dense(3) -> sigmoid -> dense(2) -> output
+    };
+    restored.load(&mut bytes.as_slice()).unwrap();
+
+    assert_eq!(trained.layers.0.weights(), restored.layers.0.weights());
+    assert_eq!(trained.layers.2.biases(), restored.layers.2.biases());
+    assert_eq!(trained.layers.4.weights(), restored.layers.4.weights());
+
+    let input = [1.0, 2.0, 3.0, 4.0];
+    let output = trained.forward(&input);
+    assert_ne!(output, [0.0; 2]);
+    assert_eq!(output, restored.forward(&input));
+}
+
+#[test]
+fn load_rejects_mismatched_shapes() {
+    let saved = nt! {
+        input(4) -> dense(8) -> relu -> dense(3) -> sigmoid -> dense(2) -> output
+    };
+    let mut bytes = Vec::new();
+    saved.save(&mut bytes).unwrap();
+
+    let mut other = nt! {
+        input(4) -> dense(8) -> relu -> dense(2) -> output
+    };
+    let err = other.load(&mut bytes.as_slice()).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+}
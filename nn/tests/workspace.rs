@@ -0,0 +1,44 @@
+use nn::network as nt;
+
+fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+#[test]
+fn forward_with_workspace_matches_forward() {
+    let mut net = nt! {
+        input(4) -> dense(8) -> relu -> dense(3) -> softmax -> output
+    };
+    for (i, w) in net
+        .layers
+        .0
+        .weights_mut()
+        .as_flattened_mut()
+        .iter_mut()
+        .enumerate()
+    {
+        *w = (i as f32).sin();
+    }
+
+    let mut workspace = net.workspace();
+    for input in [[1.0, 2.0, 3.0, 4.0], [-1.0, 0.5, 0.0, 2.0]] {
+        assert_eq!(
+            net.forward_with_workspace(&input, &mut workspace),
+            net.forward(&input)
+        );
+    }
+}
+
+// `forward` takes `&self` without shared scratch space, so one network can serve many threads
+#[test]
+fn networks_are_shareable_across_threads() {
+    let net = nt! {
+        input(2) -> dense(3) -> relu -> dense(2) -> output
+    };
+    assert_send_sync(&net);
+
+    let expected = net.forward(&[1.0, 2.0]);
+    std::thread::scope(|s| {
+        for _ in 0..2 {
+            s.spawn(|| assert_eq!(net.forward(&[1.0, 2.0]), expected));
+        }
+    });
+}